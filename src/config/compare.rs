@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+// What sets the comparison universes apart from the main one
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Variation {
+    Seed,
+    Rule,
+    Heads,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompareConfig {
+    #[serde(default = "universes")]
+    pub universes: usize,
+    #[serde(default = "vary")]
+    pub vary: Variation,
+    #[serde(default = "diff")]
+    pub diff: bool,
+}
+
+// Default functions
+fn universes() -> usize { 1 }
+fn vary() -> Variation { Variation::Seed }
+fn diff() -> bool { false }
+
+impl Default for CompareConfig {
+    fn default() -> Self {
+        Self {
+            universes: universes(),
+            vary: vary(),
+            diff: diff(),
+        }
+    }
+}
+
+impl CompareConfig {
+    pub const MAX_UNIVERSES: usize = 4;
+    const TURNS: &'static [char] = &['L', 'R', 'U', 'D'];
+
+    // Seed, rule and head count of comparison universe `index`, counted from 1
+    pub fn variant(&self, index: usize, seed: &str, rule: &str, heads: usize) -> (String, String, usize) {
+        match self.vary {
+            Variation::Seed => (format!("{}-{}", seed, index), rule.to_string(), heads),
+            Variation::Rule => (seed.to_string(), Self::mutate_rule(rule, seed, index), heads),
+            Variation::Heads => (seed.to_string(), rule.to_string(), (heads + index).min(256)),
+        }
+    }
+
+    // Swaps one relative turn for another, picked deterministically from seed and index
+    fn mutate_rule(rule: &str, seed: &str, index: usize) -> String {
+        let turns: Vec<usize> = rule.char_indices()
+            .filter(|&(_, c)| Self::TURNS.contains(&c))
+            .map(|(i, _)| i)
+            .collect();
+        if turns.is_empty() {
+            return rule.to_string();
+        }
+
+        let seed_hash = seed.bytes().fold(index as u64, |h, b| h.wrapping_mul(31).wrapping_add(b as u64));
+        let mut rng = StdRng::seed_from_u64(seed_hash);
+        let position = turns[rng.random_range(0..turns.len())];
+        let old = rule[position..].chars().next().unwrap_or('R');
+        let choices: Vec<char> = Self::TURNS.iter().copied().filter(|&c| c != old).collect();
+        let new = choices[rng.random_range(0..choices.len())];
+
+        let mut mutated = rule.to_string();
+        mutated.replace_range(position..position + 1, &new.to_string());
+        mutated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_variants_differ_by_one_turn_and_are_stable() {
+        let config = CompareConfig { vary: Variation::Rule, ..Default::default() };
+        let (seed, rule, heads) = config.variant(1, "abc", "RLLR", 3);
+        assert_eq!((seed.as_str(), heads), ("abc", 3));
+        let changed = rule.chars().zip("RLLR".chars()).filter(|(a, b)| a != b).count();
+        assert_eq!(changed, 1, "{rule}");
+        assert_eq!(rule, config.variant(1, "abc", "RLLR", 3).1);
+        // Nothing to swap in standard notation numbers
+        assert_eq!(config.variant(1, "abc", "{{{1,2,0}}}", 3).1, "{{{1,2,0}}}");
+    }
+
+    #[test]
+    fn seed_and_head_variants() {
+        let seeds = CompareConfig::default();
+        assert_eq!(seeds.variant(2, "abc", "RL", 3), ("abc-2".to_string(), "RL".to_string(), 3));
+        let heads = CompareConfig { vary: Variation::Heads, ..Default::default() };
+        assert_eq!(heads.variant(3, "abc", "RL", 255).2, 256);
+    }
+}
@@ -26,6 +26,36 @@ pub struct ControlsConfig {
     pub randomize: String,
     #[serde(default = "step_key")]
     pub step: String,
+    #[serde(default = "split_key")]
+    pub split: String,
+    #[serde(default = "diff_key")]
+    pub diff: String,
+    #[serde(default = "stats_key")]
+    pub stats: String,
+    #[serde(default = "heatmap_key")]
+    pub heatmap: String,
+    #[serde(default = "mouse_key")]
+    pub mouse: String,
+    #[serde(default = "paint_state_key")]
+    pub paint_state: String,
+    #[serde(default = "head_direction_key")]
+    pub head_direction: String,
+    #[serde(default = "presets_key")]
+    pub presets: String,
+    #[serde(default = "favourite_key")]
+    pub favourite: String,
+    #[serde(default = "history_key")]
+    pub history: String,
+    #[serde(default = "history_back_key")]
+    pub history_back: String,
+    #[serde(default = "history_forward_key")]
+    pub history_forward: String,
+    #[serde(default = "playlist_next_key")]
+    pub playlist_next: String,
+    #[serde(default = "playlist_previous_key")]
+    pub playlist_previous: String,
+    #[serde(default = "playlist_pause_key")]
+    pub playlist_pause: String,
 }
 
 // Default functions
@@ -41,6 +71,21 @@ fn seed_key() -> String { "s".to_string() }
 fn rule_key() -> String { "n".to_string() }
 fn randomize_key() -> String { "R".to_string() }
 fn step_key() -> String { ".".to_string() }
+fn split_key() -> String { "v".to_string() }
+fn diff_key() -> String { "d".to_string() }
+fn stats_key() -> String { "t".to_string() }
+fn heatmap_key() -> String { "m".to_string() }
+fn mouse_key() -> String { "i".to_string() }
+fn paint_state_key() -> String { "p".to_string() }
+fn head_direction_key() -> String { "o".to_string() }
+fn presets_key() -> String { "l".to_string() }
+fn favourite_key() -> String { "f".to_string() }
+fn history_key() -> String { "H".to_string() }
+fn history_back_key() -> String { "[".to_string() }
+fn history_forward_key() -> String { "]".to_string() }
+fn playlist_next_key() -> String { ">".to_string() }
+fn playlist_previous_key() -> String { "<".to_string() }
+fn playlist_pause_key() -> String { "P".to_string() }
 
 impl Default for ControlsConfig {
     fn default() -> Self {
@@ -57,6 +102,21 @@ impl Default for ControlsConfig {
             randomize_rule: rule_key(),
             randomize: randomize_key(),
             step: step_key(),
+            split: split_key(),
+            diff: diff_key(),
+            stats: stats_key(),
+            heatmap: heatmap_key(),
+            mouse: mouse_key(),
+            paint_state: paint_state_key(),
+            head_direction: head_direction_key(),
+            presets: presets_key(),
+            favourite: favourite_key(),
+            history: history_key(),
+            history_back: history_back_key(),
+            history_forward: history_forward_key(),
+            playlist_next: playlist_next_key(),
+            playlist_previous: playlist_previous_key(),
+            playlist_pause: playlist_pause_key(),
         }
     }
 }
@@ -1,7 +1,23 @@
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
-use super::validation;
-use crate::machine::rules::Direction;
+use super::palette::{Palette, PaletteSpan};
+use super::glyphs::{self, DirectionChars};
+use super::validation::parse_color;
+use crate::render::background::FALLBACK_BACKGROUND;
+use crate::machine::rules::{self, Direction};
+
+// Colours the terminal can show, auto reads COLORTERM and TERM
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorDepth {
+    Auto,
+    Truecolor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+    Mono,
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DisplayConfig {
@@ -9,6 +25,10 @@ pub struct DisplayConfig {
     pub keycast: bool,
     #[serde(default = "colors")]
     pub colors: Vec<String>,
+    #[serde(default = "palette")]
+    pub palette: String,
+    #[serde(default = "color_depth")]
+    pub color_depth: ColorDepth,
     #[serde(default = "fade_trail_color")]
     pub fade_trail_color: String,
     #[serde(default = "state_based_colors")]
@@ -23,10 +43,22 @@ pub struct DisplayConfig {
     pub direction_based_chars: bool,
     #[serde(default = "head_char")]
     pub head_char: Vec<String>,
+    #[serde(default)]
+    pub direction_chars: DirectionChars,
     #[serde(default = "trail_char")]
     pub trail_char: Vec<String>,
     #[serde(default = "cell_char")]
     pub cell_char: String,
+    #[serde(default = "state_chars")]
+    pub state_chars: Vec<String>,
+    #[serde(default = "head_markers")]
+    pub head_markers: bool,
+    #[serde(default = "heatmap_gradient")]
+    pub heatmap_gradient: Vec<String>,
+    #[serde(default = "cell_age_fade")]
+    pub cell_age_fade: u64,
+    #[serde(default = "cell_age_color")]
+    pub cell_age_color: String,
     
     // Cached character data
     #[serde(skip)]
@@ -35,12 +67,28 @@ pub struct DisplayConfig {
     pub trail_char_data: Vec<CharData>,
     #[serde(skip)]
     pub cell_char_data: CharData,
+    #[serde(skip)]
+    pub state_char_data: Vec<CharData>,
+    // Glyph per (previous, current) direction pair, see glyphs::glyph_index
+    #[serde(skip)]
+    pub direction_glyphs: Vec<String>,
+    #[serde(skip)]
+    pub direction_char_data: Vec<CharData>,
+    #[serde(skip)]
+    pub palette_data: Palette,
+    #[serde(skip)]
+    pub heatmap_stops: Vec<Color>,
+    // Reported by the terminal at startup, if it answered
+    #[serde(skip)]
+    pub background: Option<Color>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct CharData {
     pub chars: Vec<char>,
     pub is_single_char: bool,
+    // One glyph filling both columns of a cell on its own
+    pub is_wide: bool,
 }
 
 impl CharData {
@@ -48,11 +96,16 @@ impl CharData {
         let chars: Vec<char> = s.chars().collect();
         Self {
             is_single_char: chars.len() == 1,
+            is_wide: chars.len() == 1 && display_width(s) == 2,
             chars,
         }
     }
 }
 
+pub fn display_width(s: &str) -> usize {
+    ratatui::text::Span::raw(s).width()
+}
+
 // Default functions
 fn keycast() -> bool { false }
 fn colors() -> Vec<String> {
@@ -65,6 +118,8 @@ fn colors() -> Vec<String> {
         "194".to_string(),
     ]
 }
+fn palette() -> String { "list".to_string() }
+fn color_depth() -> ColorDepth { ColorDepth::Auto }
 fn state_based_colors() -> bool { false }
 fn live_colors() -> bool { false }
 fn head_char() -> Vec<String> { 
@@ -74,30 +129,52 @@ fn trail_char() -> Vec<String> {
     vec!["▓▓".to_string()] 
 }
 fn cell_char() -> String { "░░".to_string() }
+fn state_chars() -> Vec<String> { Vec::new() }
 fn direction_based_chars() -> bool { false }
 fn randomize_heads() -> bool { false }
 fn randomize_trails() -> bool { false }
 fn fade_trail_color() -> String { String::new() }
+fn head_markers() -> bool { true }
+fn cell_age_fade() -> u64 { 0 }
+fn cell_age_color() -> String { "#000000".to_string() }
+fn heatmap_gradient() -> Vec<String> {
+    vec!["#1d1b3a".to_string(), "#7a2e8e".to_string(), "#e0475b".to_string(), "#ffd166".to_string()]
+}
 
 impl Default for DisplayConfig {
     fn default() -> Self {
         let mut config = Self {
             keycast: keycast(),
             colors: colors(),
+            palette: palette(),
+            color_depth: color_depth(),
             fade_trail_color: fade_trail_color(),
             state_based_colors: state_based_colors(),
             live_colors: live_colors(),
             head_char: head_char(),
+            direction_chars: DirectionChars::default(),
             trail_char: trail_char(),
             cell_char: cell_char(),
+            state_chars: state_chars(),
             randomize_heads: randomize_heads(),
             randomize_trails: randomize_trails(),
             direction_based_chars: direction_based_chars(),
+            head_markers: head_markers(),
+            heatmap_gradient: heatmap_gradient(),
+            cell_age_fade: cell_age_fade(),
+            cell_age_color: cell_age_color(),
             head_char_data: Vec::new(),
             trail_char_data: Vec::new(),
             cell_char_data: CharData::new(""),
+            state_char_data: Vec::new(),
+            direction_glyphs: Vec::new(),
+            direction_char_data: Vec::new(),
+            palette_data: Palette::default(),
+            heatmap_stops: Vec::new(),
+            background: None,
         };
         config.cache_char_data();
+        config.cache_palette();
         config
     }
 }
@@ -113,107 +190,77 @@ impl DisplayConfig {
             .collect();
             
         self.cell_char_data = CharData::new(&self.cell_char);
+        self.state_char_data = self.state_chars.iter()
+            .map(|s| CharData::new(s))
+            .collect();
+
+        self.direction_glyphs = glyphs::build_table(&self.direction_chars, &self.head_char);
+        self.direction_char_data = self.direction_glyphs.iter()
+            .map(|s| CharData::new(s))
+            .collect();
     }
 
-    pub fn get_cell_color(&self, cell_state: char, head_index: usize) -> Color {
+    pub fn cache_palette(&mut self) {
+        self.palette_data = Palette::resolve(&self.palette, &self.colors, self.background);
+        self.heatmap_stops = self.heatmap_gradient.iter().map(|color| parse_color(color)).collect();
+    }
+
+    // Settings that need the terminal background asked for
+    pub fn wants_background(&self) -> bool {
+        self.fade_trail_color == "auto" || self.cell_age_color == "auto" || self.palette == "auto"
+    }
+
+    // "auto" stands for the terminal background
+    pub fn resolve_color(&self, color: &str) -> Color {
+        if color == "auto" {
+            self.background.unwrap_or(FALLBACK_BACKGROUND)
+        } else {
+            parse_color(color)
+        }
+    }
+
+    pub fn get_cell_color(&self, cell_state: char, head_index: usize, span: PaletteSpan) -> Color {
         if self.state_based_colors {
             // Map colors to states
-            let cell_index = (cell_state as u8).saturating_sub(b'A') as usize;
-            if !self.colors.is_empty() {
-                parse_color(&self.colors[cell_index % self.colors.len()])
-            } else {
-                Color::White
-            }
+            let cell_index = rules::state_index(cell_state).unwrap_or(0);
+            self.palette_data.color(cell_index, span.states)
         } else {
             // Map colors to heads
-            if !self.colors.is_empty() {
-                parse_color(&self.colors[head_index % self.colors.len()])
-            } else {
-                Color::White
-            }
+            self.palette_data.color(head_index, span.heads)
         }
     }
 
+    // Glyph for a cell state, cell_char when no state_chars are set
+    pub fn get_state_char_data(&self, cell_state: char) -> &CharData {
+        if self.state_char_data.is_empty() {
+            return &self.cell_char_data;
+        }
+        let cell_index = rules::state_index(cell_state).unwrap_or(0);
+        &self.state_char_data[cell_index % self.state_char_data.len()]
+    }
+
     pub fn should_render_cell(&self, cell_state: char) -> bool {
         self.state_based_colors || cell_state != 'A'
     }
 
-    pub fn get_head_color(&self, head_index: usize) -> Color {
-        if self.state_based_colors && !self.live_colors {
-            // Cycle through colors by head index
-            if !self.colors.is_empty() {
-                parse_color(&self.colors[head_index % self.colors.len()])
-            } else {
-                Color::White
-            }
-        } else if self.state_based_colors && self.live_colors {
-            if !self.colors.is_empty() {
-                parse_color(&self.colors[0])
-            } else {
-                Color::White
-            }
+    pub fn get_head_color(&self, head_index: usize, span: PaletteSpan) -> Color {
+        if self.state_based_colors && self.live_colors {
+            // Starts on the blank state colour, then follows what it writes
+            self.palette_data.color(0, span.states)
         } else {
-            // Head-based
-            if !self.colors.is_empty() {
-                parse_color(&self.colors[head_index % self.colors.len()])
-            } else {
-                Color::White
-            }
+            self.palette_data.color(head_index, span.heads)
         }
     }
 
-    pub fn get_head_char_index(&self, head_index: usize, direction: Direction, previous_direction: Option<Direction>) -> usize {
+    pub fn get_head_char_data(&self, head_index: usize, direction: Direction, previous_direction: Option<Direction>) -> &CharData {
         if self.direction_based_chars {
-            let index = self.get_direction_char_index(direction, previous_direction);
-            index % self.head_char_data.len()
+            &self.direction_char_data[glyphs::glyph_index(previous_direction, direction)]
         } else {
-            head_index % self.head_char_data.len()
+            &self.head_char_data[head_index % self.head_char_data.len()]
         }
     }
 
-    pub fn get_direction_char_index(&self, current_dir: Direction, prev_dir: Option<Direction>) -> usize {
-        match (prev_dir, current_dir) {
-            // Straight
-            (Some(Direction::Up), Direction::Up) => 0,              // │
-            (Some(Direction::Up), Direction::Down) => 0,
-            (Some(Direction::Down), Direction::Down) => 0,
-            (Some(Direction::Down), Direction::Up) => 0,
-            (Some(Direction::Left), Direction::Left) => 1,          // ──
-            (Some(Direction::Left), Direction::Right) => 1,
-            (Some(Direction::Right), Direction::Right) => 1,
-            (Some(Direction::Right), Direction::Left) => 1,
-
-            // Turns
-            (Some(Direction::Up), Direction::Right) => 2,           //┌
-            (Some(Direction::Left), Direction::Down) => 2,
-            (Some(Direction::Up), Direction::Left) => 3,            // ┐
-            (Some(Direction::Right), Direction::Down) => 3,
-            (Some(Direction::Down), Direction::Right) => 4,         // └ 
-            (Some(Direction::Left), Direction::Up) => 4,
-            (Some(Direction::Down), Direction::Left) => 5,          // ┘
-            (Some(Direction::Right), Direction::Up) => 5,
-
-            // Diagonals
-            (Some(Direction::UpRight), Direction::UpRight) => 6,    // ⟋
-            (Some(Direction::UpRight), Direction::DownLeft) => 6,
-            (Some(Direction::DownLeft), Direction::DownLeft) => 6,
-            (Some(Direction::DownLeft), Direction::UpRight) => 6,
-            (Some(Direction::UpLeft), Direction::UpLeft) => 7,      // ⟍
-            (Some(Direction::UpLeft), Direction::DownRight) => 7,
-            (Some(Direction::DownRight), Direction::DownRight) => 7,
-            (Some(Direction::DownRight), Direction::UpLeft) => 7,
-
-            // Diagonal turns
-            (Some(Direction::UpRight), Direction::DownRight) => 8,  // ⟋⟍
-            (Some(Direction::UpLeft), Direction::DownLeft) => 8,
-            (Some(Direction::DownRight), Direction::UpRight) => 9,  // ⟍⟋
-            (Some(Direction::DownLeft), Direction::UpLeft) => 9,
-            
-            _ => 0,
-        }
+    pub fn direction_glyph(&self, previous_direction: Option<Direction>, direction: Direction) -> &str {
+        &self.direction_glyphs[glyphs::glyph_index(previous_direction, direction)]
     }
 }
-
-pub fn parse_color(color_str: &str) -> Color {
-    validation::parse_color(color_str)
-}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use crate::machine::rules::Direction;
+
+// Table rows and columns run clockwise from up
+pub const DIRECTION_ORDER: [Direction; 8] = [
+    Direction::Up, Direction::UpRight, Direction::Right, Direction::DownRight,
+    Direction::Down, Direction::DownLeft, Direction::Left, Direction::UpLeft,
+];
+
+pub const SET_NAMES: [&str; 5] = ["light", "heavy", "rounded", "double", "ascii"];
+
+// Glyphs per set, one per shape below
+const SHAPES: usize = 12;
+const LIGHT: [&str; SHAPES] = ["│", "──", "┌─", "┐", "└─", "┘", "⟋", "⟍", "⟋⟍", "⟍⟋", "⟩", "⟨"];
+const HEAVY: [&str; SHAPES] = ["┃", "━━", "┏━", "┓", "┗━", "┛", "⟋", "⟍", "⟋⟍", "⟍⟋", "⟩", "⟨"];
+const ROUNDED: [&str; SHAPES] = ["│", "──", "╭─", "╮", "╰─", "╯", "⟋", "⟍", "⟋⟍", "⟍⟋", "⟩", "⟨"];
+const DOUBLE: [&str; SHAPES] = ["║", "══", "╔═", "╗", "╚═", "╝", "╱", "╲", "╱╲", "╲╱", "⟩", "⟨"];
+const ASCII: [&str; SHAPES] = ["|", "--", "+-", "+", "+-", "+", "/", "\\", "/\\", "\\/", ">", "<"];
+
+// Glyphs head_char supplied before sets existed, shapes past it fall back to a straight line
+pub const LEGACY_SHAPES: usize = 10;
+
+// Named set, or rows by previous direction holding a glyph per current direction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DirectionChars {
+    Named(String),
+    Table(Vec<Vec<String>>),
+}
+
+impl Default for DirectionChars {
+    // Empty name keeps using head_char
+    fn default() -> Self {
+        DirectionChars::Named(String::new())
+    }
+}
+
+pub fn direction_index(direction: Direction) -> usize {
+    DIRECTION_ORDER.iter().position(|&d| d == direction).unwrap_or(0)
+}
+
+// Index into a 64 glyph table, a head that hasn't turned yet goes straight
+pub fn glyph_index(previous: Option<Direction>, current: Direction) -> usize {
+    direction_index(previous.unwrap_or(current)) * 8 + direction_index(current)
+}
+
+fn straight(direction: Direction) -> usize {
+    match direction.delta() {
+        (0, _) => 0,
+        (_, 0) => 1,
+        (dx, dy) if dx * dy < 0 => 6,
+        _ => 7,
+    }
+}
+
+// Shape a path makes through a cell, entering against `previous` and leaving along `current`:
+// 0 │  1 ─  2 ┌  3 ┐  4 └  5 ┘  6 ⟋  7 ⟍  8 peak  9 valley  10 >  11 <
+pub fn shape(previous: Direction, current: Direction) -> usize {
+    let (px, py) = previous.delta();
+    let entry = (-px, -py);
+    let exit = current.delta();
+    let diagonal = |(x, y): (i32, i32)| x != 0 && y != 0;
+    let reversed = exit == (-entry.0, -entry.1);
+
+    if diagonal(entry) != diagonal(exit) || entry == exit || reversed {
+        return straight(current);
+    }
+    if !diagonal(entry) {
+        // Orthogonal corner, named by the two sides it joins
+        let joins = |side: (i32, i32)| entry == side || exit == side;
+        return match (joins((0, 1)), joins((1, 0))) {
+            (true, true) => 2,
+            (true, false) => 3,
+            (false, true) => 4,
+            (false, false) => 5,
+        };
+    }
+    // Diagonal corners sharing an edge of the cell
+    match (entry.1 == exit.1, entry) {
+        (true, (_, 1)) => 8,
+        (true, _) => 9,
+        (false, (-1, _)) => 10,
+        (false, _) => 11,
+    }
+}
+
+fn named_set(name: &str) -> Option<[&'static str; SHAPES]> {
+    match name {
+        "light" => Some(LIGHT),
+        "heavy" => Some(HEAVY),
+        "rounded" => Some(ROUNDED),
+        "double" => Some(DOUBLE),
+        "ascii" => Some(ASCII),
+        _ => None,
+    }
+}
+
+// The 64 glyph table, head_char stands in for an empty or unknown set name
+pub fn build_table(chars: &DirectionChars, head_char: &[String]) -> Vec<String> {
+    let by_shape = |glyph: &dyn Fn(usize, Direction) -> String| -> Vec<String> {
+        DIRECTION_ORDER.iter()
+            .flat_map(|&previous| DIRECTION_ORDER.iter().map(move |&current| (previous, current)))
+            .map(|(previous, current)| glyph(shape(previous, current), current))
+            .collect()
+    };
+    match chars {
+        DirectionChars::Table(rows) => rows.iter().flatten().cloned().collect(),
+        DirectionChars::Named(name) => match named_set(name) {
+            Some(set) => by_shape(&|shape, _| set[shape].to_string()),
+            None if head_char.is_empty() => vec![String::new(); 64],
+            None => by_shape(&|shape, current| {
+                let shape = if shape < LEGACY_SHAPES { shape } else { straight(current) };
+                head_char[shape % head_char.len()].clone()
+            }),
+        },
+    }
+}
+
+pub fn validate(chars: &DirectionChars) -> Result<(), String> {
+    match chars {
+        DirectionChars::Named(name) if name.is_empty() || named_set(name).is_some() => Ok(()),
+        DirectionChars::Named(name) => Err(format!("unknown set '{}', expected one of {}", name, SET_NAMES.join(", "))),
+        DirectionChars::Table(rows) if rows.len() != 8 => Err(format!("needs 8 rows, one per previous direction, found {}", rows.len())),
+        DirectionChars::Table(rows) => match rows.iter().position(|row| row.len() != 8 || row.iter().any(|g| g.is_empty())) {
+            Some(i) => Err(format!("row {} needs 8 non-empty glyphs, one per current direction", i)),
+            None => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::*;
+
+    #[test]
+    fn shapes_cover_every_pair() {
+        assert_eq!(shape(Up, Up), 0);
+        assert_eq!(shape(Up, Down), 0);
+        assert_eq!(shape(Left, Right), 1);
+        assert_eq!(shape(Up, Right), 2);
+        assert_eq!(shape(Left, Down), 2);
+        assert_eq!(shape(Up, Left), 3);
+        assert_eq!(shape(Down, Right), 4);
+        assert_eq!(shape(Right, Up), 5);
+        assert_eq!(shape(UpRight, DownLeft), 6);
+        assert_eq!(shape(DownRight, DownRight), 7);
+        assert_eq!(shape(UpRight, DownRight), 8);
+        assert_eq!(shape(DownLeft, UpLeft), 9);
+        assert_eq!(shape(UpRight, UpLeft), 10);
+        assert_eq!(shape(DownRight, DownLeft), 10);
+        assert_eq!(shape(UpLeft, UpRight), 11);
+        // Orthogonal to diagonal draws the new heading
+        assert_eq!(shape(Up, UpRight), 6);
+        assert_eq!(shape(DownLeft, Left), 1);
+    }
+
+    #[test]
+    fn tables_come_from_sets_tables_or_head_char() {
+        let rounded = build_table(&DirectionChars::Named("rounded".to_string()), &[]);
+        assert_eq!(rounded.len(), 64);
+        assert_eq!(rounded[glyph_index(Some(Up), Right)], "╭─");
+        assert_eq!(rounded[glyph_index(None, DownLeft)], "⟋");
+
+        let legacy: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+        let table = build_table(&DirectionChars::default(), &legacy);
+        assert_eq!(table[glyph_index(Some(Right), Up)], "5");
+        assert_eq!(table[glyph_index(Some(UpRight), UpLeft)], "7");
+
+        let rows = vec![vec!["x".to_string(); 8]; 8];
+        assert!(validate(&DirectionChars::Table(rows.clone())).is_ok());
+        assert_eq!(build_table(&DirectionChars::Table(rows), &[]).len(), 64);
+        assert!(validate(&DirectionChars::Table(vec![vec!["x".to_string(); 7]; 8])).is_err());
+        assert!(validate(&DirectionChars::Named("thin".to_string())).is_err());
+        assert!(toml::from_str::<crate::config::DisplayConfig>("direction_chars = \"heavy\"").is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+use std::error::Error;
+use super::Config;
+
+// Oldest runs are dropped past this, favourites are always kept
+const MAX_RUNS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    pub rule: String,
+    pub seed: String,
+    pub heads: usize,
+    pub steps: u64,
+    // Detection status when the run was left
+    pub outcome: String,
+    pub favourite: bool,
+}
+
+impl RunRecord {
+    fn same_run(&self, other: &RunRecord) -> bool {
+        (&self.rule, &self.seed, self.heads) == (&other.rule, &other.seed, other.heads)
+    }
+
+    // favourite, heads, steps, seed, rule, outcome, tab separated
+    fn to_line(&self) -> String {
+        let clean = |s: &str| s.replace(['\t', '\n'], " ");
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            if self.favourite { "*" } else { "-" },
+            self.heads, self.steps, clean(&self.seed), clean(&self.rule), clean(&self.outcome),
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, '\t');
+        let favourite = fields.next()? == "*";
+        let heads = fields.next()?.parse().ok()?;
+        let steps = fields.next()?.parse().ok()?;
+        let seed = fields.next()?.to_string();
+        let rule = fields.next()?.to_string();
+        let outcome = fields.next().unwrap_or_default().to_string();
+        Some(Self { rule, seed, heads, steps, outcome, favourite })
+    }
+}
+
+// Every run played, oldest first, kept in the state dir
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    pub runs: Vec<RunRecord>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .map(|text| Self::from_text(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        std::fs::create_dir_all(Config::state_dir())?;
+        std::fs::write(Self::path(), self.to_text())?;
+        Ok(())
+    }
+
+    fn path() -> std::path::PathBuf {
+        Config::state_dir().join("history")
+    }
+
+    fn from_text(text: &str) -> Self {
+        Self { runs: text.lines().filter_map(RunRecord::from_line).collect() }
+    }
+
+    fn to_text(&self) -> String {
+        self.runs.iter().map(|run| run.to_line() + "\n").collect()
+    }
+
+    // A run played again straight after itself updates its entry rather than adding one
+    pub fn record(&mut self, run: RunRecord) {
+        match self.runs.last_mut() {
+            Some(last) if last.same_run(&run) => {
+                last.steps = last.steps.max(run.steps);
+                last.outcome = run.outcome;
+            }
+            _ => self.runs.push(run),
+        }
+        let mut excess = self.runs.len().saturating_sub(MAX_RUNS);
+        self.runs.retain(|run| {
+            let drop = excess > 0 && !run.favourite;
+            excess -= drop as usize;
+            !drop
+        });
+    }
+
+    pub fn toggle_favourite(&mut self, index: usize) {
+        if let Some(run) = self.runs.get_mut(index) {
+            run.favourite = !run.favourite;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(rule: &str, seed: &str, steps: u64) -> RunRecord {
+        RunRecord { rule: rule.to_string(), seed: seed.to_string(), heads: 2, steps, outcome: "Running".to_string(), favourite: false }
+    }
+
+    #[test]
+    fn history_round_trips_and_merges_repeats() {
+        let mut history = History::default();
+        history.record(run("RL", "abc", 10));
+        history.record(run("RL", "abc", 40));
+        history.record(run("L1>1,R0>0", "xyz", 5));
+        history.toggle_favourite(0);
+        assert_eq!(history.runs.len(), 2);
+        assert_eq!(history.runs[0].steps, 40);
+        assert_eq!(History::from_text(&history.to_text()), history);
+        // Lines that don't parse are skipped
+        assert_eq!(History::from_text("garbage\n").runs, vec![]);
+    }
+
+    #[test]
+    fn favourites_outlive_the_cap() {
+        let mut history = History::default();
+        history.record(run("RL", "keep", 1));
+        history.toggle_favourite(0);
+        for i in 0..MAX_RUNS + 5 {
+            history.record(run("RL", &i.to_string(), 1));
+        }
+        assert_eq!(history.runs.len(), MAX_RUNS);
+        assert_eq!(history.runs[0].seed, "keep");
+        assert_eq!(history.runs.last().unwrap().seed, (MAX_RUNS + 4).to_string());
+    }
+}
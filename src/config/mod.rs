@@ -1,15 +1,26 @@
 pub mod simulation;
 pub mod display;
 pub mod controls;
+pub mod compare;
+pub mod reactions;
+pub mod playlist;
+pub mod palette;
+pub mod glyphs;
+pub mod presets;
+pub mod history;
 pub mod validation;
 
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
-use std::{error::Error, fs, path::PathBuf};
+use std::{error::Error, fs, path::{Path, PathBuf}};
 
-pub use simulation::{SimulationConfig, SimMode};
-pub use display::{DisplayConfig, CharData};
+pub use simulation::{SimulationConfig, SimMode, HeadSchedule};
+pub use display::{DisplayConfig, CharData, ColorDepth};
 pub use controls::ControlsConfig;
+pub use compare::CompareConfig;
+pub use reactions::{Outcome, Reaction, ReactionsConfig};
+pub use playlist::{PlaylistConfig, Transition};
+pub use palette::PaletteSpan;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[derive(Default)]
@@ -20,6 +31,12 @@ pub struct Config {
     pub display: DisplayConfig,
     #[serde(default)]
     pub controls: ControlsConfig,
+    #[serde(default)]
+    pub compare: CompareConfig,
+    #[serde(default)]
+    pub reactions: ReactionsConfig,
+    #[serde(default)]
+    pub playlist: PlaylistConfig,
 }
 
 pub enum ConfigLoadResult {
@@ -39,8 +56,11 @@ impl Config {
                     Ok(mut config) => {
                         if let Err(errors) = config.validate() {
                             ConfigLoadResult::ValidationErrors(Config::default(), errors)
+                        } else if let Err(error) = config.simulation.cache_tape() {
+                            ConfigLoadResult::ValidationErrors(Config::default(), vec![error])
                         } else {
                             config.display.cache_char_data();
+                            config.display.cache_palette();
                             ConfigLoadResult::Success(config)
                         }
                     },
@@ -144,6 +164,29 @@ impl Config {
         Ok(())
     }
 
+    // Writes a tape snapshot under the state dir and returns where it went
+    pub fn save_snapshot(name: &str, contents: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let snapshot_dir = Self::state_dir().join("snapshots");
+        std::fs::create_dir_all(&snapshot_dir)?;
+
+        let file_name: String = name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let snapshot_path = snapshot_dir.join(format!("{}.txt", file_name));
+        std::fs::write(&snapshot_path, contents)?;
+
+        Ok(snapshot_path)
+    }
+
+    // A preset's rule and seed replace the ones kept in state, an empty seed means a random one
+    pub fn save_preset_state(&self) -> Result<(), Box<dyn Error>> {
+        Self::save_current_rule(&self.simulation.rule)?;
+        match self.simulation.seed.as_deref() {
+            Some(seed) if !seed.is_empty() => Self::save_current_seed(seed),
+            _ => Self::clear_current_seed(),
+        }
+    }
+
     pub fn apply_cli_overrides(rule: Option<&str>, seed: Option<&str>) {
         if let Some(rule) = rule {
             let _ = Self::save_current_rule(rule);
@@ -165,6 +208,15 @@ impl Config {
         }
     }
 
+    // Paths in the config are relative to the config dir, '~' is the home dir
+    pub fn resolve_path(path: &str) -> PathBuf {
+        match path.strip_prefix("~/").zip(dirs::home_dir()) {
+            Some((rest, home)) => home.join(rest),
+            None if Path::new(path).is_absolute() => PathBuf::from(path),
+            None => Self::config_dir().join(path),
+        }
+    }
+
     fn create_example_config(&self) -> Result<(), Box<dyn Error>> {
         let config_dir = Self::config_dir();
         fs::create_dir_all(&config_dir)?;
@@ -0,0 +1,128 @@
+use ratatui::style::Color;
+use crate::render::{background::is_light, effects};
+use super::validation::parse_color;
+
+// Built-in palettes, gradients are spread over the states or heads in use
+const VIRIDIS: [&str; 5] = ["#440154", "#3b528b", "#21918c", "#5ec962", "#fde725"];
+const MAGMA: [&str; 6] = ["#000004", "#3b0f70", "#8c2981", "#de4968", "#fe9f6d", "#fcfdbf"];
+const PLASMA: [&str; 5] = ["#0d0887", "#7e03a8", "#cc4778", "#f89540", "#f0f921"];
+const SOLARIZED: [&str; 8] = ["#b58900", "#cb4b16", "#dc322f", "#d33682", "#6c71c4", "#268bd2", "#2aa198", "#859900"];
+
+pub const PALETTE_NAMES: [&str; 8] = ["list", "auto", "gradient", "hsv", "viridis", "magma", "plasma", "solarized"];
+
+// How far "auto" pulls the colour list toward black on light backgrounds
+const LIGHT_BACKGROUND_SHADE: f32 = 0.45;
+
+// Hue step for colours past the end of a list, keeps consecutive extras far apart
+const GOLDEN_ANGLE: f32 = 137.508;
+
+// How many states and heads a palette is spread across
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteSpan {
+    pub states: usize,
+    pub heads: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Palette {
+    List(Vec<Color>),       // fixed colours, generated ones past the end
+    Gradient(Vec<Color>),   // stops sampled evenly over the span
+    Hsv,                    // evenly spaced hues over the span
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::List(Vec::new())
+    }
+}
+
+impl Palette {
+    // Unknown names fall back to the colour list, validation reports them
+    pub fn resolve(name: &str, colors: &[String], background: Option<Color>) -> Self {
+        let parse = |stops: &[&str]| stops.iter().map(|c| parse_color(c)).collect();
+        match name {
+            // The colour list, shaded so pale colours still show on a light terminal
+            "auto" if background.is_some_and(is_light) => Palette::List(
+                colors.iter()
+                    .map(|c| effects::fade_color_to_target(parse_color(c), Color::Rgb(0, 0, 0), LIGHT_BACKGROUND_SHADE))
+                    .collect()
+            ),
+            "gradient" => Palette::Gradient(colors.iter().map(|c| parse_color(c)).collect()),
+            "hsv" => Palette::Hsv,
+            "viridis" => Palette::Gradient(parse(&VIRIDIS)),
+            "magma" => Palette::Gradient(parse(&MAGMA)),
+            "plasma" => Palette::Gradient(parse(&PLASMA)),
+            "solarized" => Palette::List(parse(&SOLARIZED)),
+            _ => Palette::List(colors.iter().map(|c| parse_color(c)).collect()),
+        }
+    }
+
+    pub fn color(&self, index: usize, span: usize) -> Color {
+        let span = span.max(1);
+        match self {
+            Palette::List(colors) => colors.get(index).copied()
+                .unwrap_or_else(|| hsv_to_rgb(index as f32 * GOLDEN_ANGLE, 0.6, 0.95)),
+            Palette::Gradient(stops) => {
+                let level = if span == 1 { 0.0 } else { (index % span) as f32 / (span - 1) as f32 };
+                effects::gradient_color(stops, level)
+            }
+            Palette::Hsv => hsv_to_rgb((index % span) as f32 * 360.0 / span as f32, 0.6, 0.95),
+        }
+    }
+}
+
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> Color {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let channel = |c: f32| ((c + m) * 255.0).round() as u8;
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_resolve_by_name() {
+        let colors = vec!["#000000".to_string(), "#ff0000".to_string()];
+        let list = Palette::resolve("list", &colors, None);
+        assert_eq!(list.color(1, 4), Color::Rgb(255, 0, 0));
+        // Past the end of the list colours are generated rather than repeated
+        assert_ne!(list.color(2, 4), list.color(0, 4));
+        assert_ne!(list.color(2, 4), list.color(3, 4));
+
+        let gradient = Palette::resolve("gradient", &colors, None);
+        assert_eq!(gradient.color(0, 3), Color::Rgb(0, 0, 0));
+        assert_eq!(gradient.color(1, 3), Color::Rgb(127, 0, 0));
+        assert_eq!(gradient.color(2, 3), Color::Rgb(255, 0, 0));
+
+        assert_eq!(Palette::resolve("viridis", &[], None).color(0, 10), Color::Rgb(0x44, 0x01, 0x54));
+        assert_eq!(Palette::resolve("viridis", &[], None).color(9, 10), Color::Rgb(0xfd, 0xe7, 0x25));
+
+        // Auto keeps the list on dark terminals and darkens it on light ones
+        let white = vec!["#ffffff".to_string()];
+        assert_eq!(Palette::resolve("auto", &white, Some(Color::Rgb(0, 0, 0))).color(0, 1), Color::Rgb(255, 255, 255));
+        assert_eq!(Palette::resolve("auto", &white, Some(Color::Rgb(250, 250, 250))).color(0, 1), Color::Rgb(140, 140, 140));
+    }
+
+    #[test]
+    fn hsv_spreads_hues_over_the_span() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), Color::Rgb(255, 0, 0));
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), Color::Rgb(0, 255, 0));
+        assert_eq!(hsv_to_rgb(240.0, 1.0, 1.0), Color::Rgb(0, 0, 255));
+        let colors: Vec<Color> = (0..64).map(|i| Palette::Hsv.color(i, 64)).collect();
+        for (i, color) in colors.iter().enumerate() {
+            assert!(!colors[i + 1..].contains(color));
+        }
+    }
+}
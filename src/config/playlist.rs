@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use super::{Config, presets::{self, Preset}};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistConfig {
+    // Rules stepped through by the next_rule reaction, in order, unless there are entries
+    #[serde(default)]
+    pub rules: Vec<String>,
+    // Slideshow, played in order and looped whenever there are entries
+    #[serde(default)]
+    pub entries: Vec<PlaylistEntry>,
+    #[serde(default = "seconds")]
+    pub seconds: f64,
+    #[serde(default = "steps")]
+    pub steps: u64,
+    #[serde(default = "on_detection")]
+    pub on_detection: bool,
+    #[serde(default = "transition")]
+    pub transition: Transition,
+    #[serde(default = "transition_ms")]
+    pub transition_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Cut,            // the next entry replaces the grid at once
+    Fade,           // the old grid fades into the background first
+}
+
+// One slideshow slide: a preset to start from, with rule, seed and settings laid over it
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub preset: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub rule: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub seed: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heads: Option<usize>,
+    // Overrides of playlist.seconds and playlist.steps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<u64>,
+    // Further settings, as a preset holds them
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub simulation: Table,
+    #[serde(default, skip_serializing_if = "Table::is_empty")]
+    pub display: Table,
+}
+
+// Default functions
+fn seconds() -> f64 { 60.0 }
+fn steps() -> u64 { 0 }
+fn on_detection() -> bool { true }
+fn transition() -> Transition { Transition::Fade }
+fn transition_ms() -> u64 { 1000 }
+
+impl Default for PlaylistConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            entries: Vec::new(),
+            seconds: seconds(),
+            steps: steps(),
+            on_detection: on_detection(),
+            transition: transition(),
+            transition_ms: transition_ms(),
+        }
+    }
+}
+
+impl PlaylistConfig {
+    // Rule after `position`, starting from the top when nothing has played yet
+    pub fn next(&self, position: Option<usize>) -> Option<(usize, &str)> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let index = position.map_or(0, |p| (p + 1) % self.rules.len());
+        Some((index, &self.rules[index]))
+    }
+
+    // Time and step limits of an entry, 0 for none
+    pub fn limits(&self, index: usize) -> (f64, u64) {
+        let entry = self.entries.get(index);
+        (
+            entry.and_then(|e| e.seconds).unwrap_or(self.seconds),
+            entry.and_then(|e| e.steps).unwrap_or(self.steps),
+        )
+    }
+}
+
+impl PlaylistEntry {
+    // The config this entry plays under
+    pub fn apply(&self, config: &Config) -> Result<Config, String> {
+        let base = match self.preset.as_str() {
+            "" => config.clone(),
+            name => presets::find(name)?.apply(config)?,
+        };
+        let mut simulation = self.simulation.clone();
+        for (key, value) in [("rule", &self.rule), ("seed", &self.seed)] {
+            if !value.is_empty() {
+                simulation.insert(key.to_string(), Value::String(value.clone()));
+            }
+        }
+        if let Some(heads) = self.heads {
+            simulation.insert("heads".to_string(), Value::Integer(heads as i64));
+        }
+        let settings = Table::from_iter([
+            ("simulation".to_string(), Value::Table(simulation)),
+            ("display".to_string(), Value::Table(self.display.clone())),
+        ]);
+        Preset::from_settings("playlist entry", settings).apply(&base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_lay_their_settings_over_the_config() {
+        let playlist: PlaylistConfig = toml::from_str(r##"
+            seconds = 30
+            [[entries]]
+            preset = "matrix"
+            seed = "rain"
+            steps = 5000
+            [[entries]]
+            rule = "RL"
+            heads = 3
+            display = { colors = ["#ffffff"] }
+        "##).unwrap();
+        assert_eq!(playlist.transition, Transition::Fade);
+        assert_eq!(playlist.limits(0), (30.0, 5000));
+        assert_eq!(playlist.limits(1), (30.0, 0));
+
+        let mut config = Config::default();
+        config.display.keycast = true;
+        let matrix = playlist.entries[0].apply(&config).unwrap();
+        assert_eq!(matrix.simulation.rule, "S");
+        assert_eq!(matrix.simulation.seed.as_deref(), Some("rain"));
+        let langton = playlist.entries[1].apply(&config).unwrap();
+        assert_eq!((langton.simulation.rule.as_str(), langton.simulation.heads), ("RL", 3));
+        assert_eq!(langton.display.colors, vec!["#ffffff"]);
+        assert!(langton.display.keycast);
+
+        let broken = PlaylistEntry { heads: Some(0), ..PlaylistEntry::default() };
+        assert!(broken.apply(&config).is_err());
+        let missing = PlaylistEntry { preset: "nope".to_string(), ..PlaylistEntry::default() };
+        assert!(missing.apply(&config).is_err());
+    }
+}
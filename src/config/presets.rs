@@ -0,0 +1,176 @@
+use std::path::Path;
+use toml::{Table, Value};
+use super::Config;
+
+// Shipped with trmt, one per curated example
+const BUILTIN: [(&str, &str); 8] = [
+    ("builders", include_str!("../../presets/builders.toml")),
+    ("critter_highway", include_str!("../../presets/critter_highway.toml")),
+    ("dotgrid", include_str!("../../presets/dotgrid.toml")),
+    ("factory", include_str!("../../presets/factory.toml")),
+    ("matrix", include_str!("../../presets/matrix.toml")),
+    ("pipedream", include_str!("../../presets/pipedream.toml")),
+    ("scaffold", include_str!("../../presets/scaffold.toml")),
+    ("transistor", include_str!("../../presets/transistor.toml")),
+];
+
+// Config tables merged over the user's config, described by an optional [preset] table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+    settings: Table,
+}
+
+impl Preset {
+    pub fn parse(name: &str, text: &str, builtin: bool) -> Result<Self, String> {
+        let mut settings: Table = toml::from_str(text).map_err(|e| format!("{}: {}", name, e))?;
+        let description = settings.remove("preset")
+            .and_then(|meta| meta.get("description")?.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let preset = Self { name: name.to_string(), description, builtin, settings };
+        preset.apply(&Config::default()).map_err(|e| format!("{}: {}", name, e))?;
+        Ok(preset)
+    }
+
+    // Settings built in code rather than read from a file
+    pub fn from_settings(name: &str, settings: Table) -> Self {
+        Self { name: name.to_string(), description: String::new(), builtin: false, settings }
+    }
+
+    fn setting(&self, section: &str, key: &str) -> Option<&Value> {
+        self.settings.get(section)?.get(key)
+    }
+
+    pub fn rule(&self) -> Option<&str> {
+        self.setting("simulation", "rule")?.as_str()
+    }
+
+    pub fn seed(&self) -> Option<&str> {
+        self.setting("simulation", "seed")?.as_str().filter(|seed| !seed.is_empty())
+    }
+
+    pub fn heads(&self) -> Option<i64> {
+        self.setting("simulation", "heads")?.as_integer()
+    }
+
+    pub fn colors(&self) -> Vec<&str> {
+        self.setting("display", "colors")
+            .and_then(Value::as_array)
+            .map(|colors| colors.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    // The config with this preset's settings laid over it
+    pub fn apply(&self, config: &Config) -> Result<Config, String> {
+        let Value::Table(mut merged) = Value::try_from(config).map_err(|e| e.to_string())? else {
+            return Err("config is not a table".to_string());
+        };
+        merge(&mut merged, &self.settings);
+        let mut applied: Config = Value::Table(merged).try_into().map_err(|e: toml::de::Error| e.message().to_string())?;
+        applied.validate().map_err(|errors| errors.join(", "))?;
+        applied.display.background = config.display.background;
+        applied.display.cache_char_data();
+        applied.display.cache_palette();
+        applied.simulation.cache_tape()?;
+        Ok(applied)
+    }
+}
+
+fn merge(base: &mut Table, over: &Table) {
+    for (key, value) in over {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(over)) => merge(base, over),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+pub fn builtin() -> Vec<Preset> {
+    BUILTIN.iter()
+        .filter_map(|(name, text)| Preset::parse(name, text, true).ok())
+        .collect()
+}
+
+// Built-in presets, then the user's from presets/ in the config dir. A user preset
+// with a built-in's name replaces it. Presets that don't load are reported
+pub fn load_all() -> (Vec<Preset>, Vec<String>) {
+    let mut presets = builtin();
+    let (user, errors) = load_dir(&Config::resolve_path("presets"));
+    for preset in user {
+        presets.retain(|p| p.name != preset.name);
+        presets.push(preset);
+    }
+    (presets, errors)
+}
+
+fn load_dir(dir: &Path) -> (Vec<Preset>, Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut paths: Vec<_> = entries.filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    paths.sort();
+
+    let (mut presets, mut errors) = (Vec::new(), Vec::new());
+    for path in paths {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        match std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", name, e))
+            .and_then(|text| Preset::parse(&name, &text, false))
+        {
+            Ok(preset) => presets.push(preset),
+            Err(e) => errors.push(e),
+        }
+    }
+    (presets, errors)
+}
+
+pub fn find(name: &str) -> Result<Preset, String> {
+    let (presets, errors) = load_all();
+    if let Some(preset) = presets.iter().find(|p| p.name == name) {
+        return Ok(preset.clone());
+    }
+    if let Some(error) = errors.into_iter().find(|e| e.starts_with(&format!("{}:", name))) {
+        return Err(error);
+    }
+    let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+    Err(format!("no preset named '{}', try one of {}", name, names.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_presets_load_and_apply() {
+        for (name, text) in BUILTIN {
+            let preset = Preset::parse(name, text, true).unwrap();
+            assert!(!preset.description.is_empty(), "{}", name);
+            assert!(preset.rule().is_some(), "{}", name);
+        }
+        let matrix = Preset::parse("matrix", BUILTIN[4].1, true).unwrap();
+        let mut config = Config::default();
+        config.controls.quit = "Q".to_string();
+        let applied = matrix.apply(&config).unwrap();
+        assert_eq!(applied.simulation.heads, 128);
+        assert_eq!(applied.simulation.rule, "S");
+        assert!(applied.display.randomize_heads);
+        // Settings the preset leaves out stay as the user had them
+        assert_eq!(applied.controls.quit, "Q");
+        assert_eq!(matrix.colors().len(), 4);
+        assert_eq!(matrix.seed(), None);
+    }
+
+    #[test]
+    fn broken_presets_are_reported() {
+        assert!(Preset::parse("bad", "[simulation]\nheads = 0\n", false).is_err());
+        assert!(Preset::parse("bad", "[simulation\n", false).is_err());
+        let minimal = Preset::parse("minimal", "[simulation]\nrule = \"RL\"\n", false).unwrap();
+        assert_eq!(minimal.description, "");
+        assert_eq!(minimal.heads(), None);
+    }
+}
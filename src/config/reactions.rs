@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use super::{SimMode, SimulationConfig};
+
+// How a run ended, as far as reactions are concerned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Stall,
+    Cycle,
+    Highway,
+    Timeout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reaction {
+    Halt,           // pause where the run ended
+    Restart,        // replay the same seed and rule
+    NewSeed,        // random seed, same rule
+    NewRule,        // random rule, same seed
+    NextRule,       // next rule from the playlist
+    Snapshot,       // save the tape to disk, then halt
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReactionsConfig {
+    #[serde(default)]
+    pub on_stall: Option<Reaction>,
+    #[serde(default)]
+    pub on_cycle: Option<Reaction>,
+    #[serde(default)]
+    pub on_highway: Option<Reaction>,
+    #[serde(default)]
+    pub on_timeout: Option<Reaction>,
+    #[serde(default = "max_steps")]
+    pub max_steps: u64,
+}
+
+// Default functions
+fn max_steps() -> u64 { 0 }
+
+impl Default for ReactionsConfig {
+    fn default() -> Self {
+        Self {
+            on_stall: None,
+            on_cycle: None,
+            on_highway: None,
+            on_timeout: None,
+            max_steps: max_steps(),
+        }
+    }
+}
+
+impl ReactionsConfig {
+    // Unset outcomes fall back to simulation.mode, highways only when highway_reaction is on
+    pub fn for_outcome(&self, outcome: Outcome, simulation: &SimulationConfig) -> Option<Reaction> {
+        let mode = match simulation.mode {
+            SimMode::Halt => Reaction::Halt,
+            SimMode::Loop => Reaction::Restart,
+        };
+        match outcome {
+            Outcome::Stall => Some(self.on_stall.unwrap_or(mode)),
+            Outcome::Cycle => Some(self.on_cycle.unwrap_or(mode)),
+            Outcome::Highway => self.on_highway.or(simulation.highway_reaction.then_some(mode)),
+            Outcome::Timeout => Some(self.on_timeout.unwrap_or(mode)),
+        }
+    }
+
+    pub fn reacts_to_highways(&self, simulation: &SimulationConfig) -> bool {
+        self.for_outcome(Outcome::Highway, simulation).is_some()
+    }
+
+    pub fn timed_out(&self, steps: u64) -> bool {
+        self.max_steps > 0 && steps >= self.max_steps
+    }
+
+    pub fn uses(&self, reaction: Reaction) -> bool {
+        [self.on_stall, self.on_cycle, self.on_highway, self.on_timeout].contains(&Some(reaction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_outcomes_follow_the_mode() {
+        let mut simulation = SimulationConfig { mode: SimMode::Loop, ..Default::default() };
+        let reactions = ReactionsConfig { on_cycle: Some(Reaction::NewRule), ..Default::default() };
+        assert_eq!(reactions.for_outcome(Outcome::Stall, &simulation), Some(Reaction::Restart));
+        assert_eq!(reactions.for_outcome(Outcome::Cycle, &simulation), Some(Reaction::NewRule));
+        assert_eq!(reactions.for_outcome(Outcome::Highway, &simulation), None);
+        simulation.highway_reaction = true;
+        assert_eq!(reactions.for_outcome(Outcome::Highway, &simulation), Some(Reaction::Restart));
+    }
+
+    #[test]
+    fn reactions_parse_from_toml() {
+        let reactions: ReactionsConfig = toml::from_str("on_stall = \"new_seed\"\non_timeout = \"snapshot\"\nmax_steps = 500").unwrap();
+        assert_eq!(reactions.on_stall, Some(Reaction::NewSeed));
+        assert_eq!(reactions.on_timeout, Some(Reaction::Snapshot));
+        assert!(reactions.timed_out(500));
+        assert!(!ReactionsConfig::default().timed_out(u64::MAX));
+    }
+}
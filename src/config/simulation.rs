@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use rand::Rng;
 use std::collections::{HashSet};
+use crate::machine::InitialTape;
 
 // What to do when detection proves a run is done
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -10,6 +11,15 @@ pub enum SimMode {
     Loop,
 }
 
+// Order in which heads act within a step
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadSchedule {
+    Sequential,     // each head reads, writes and moves before the next
+    Lockstep,       // all heads read first, then all write and move
+    Random,         // sequential, shuffled by seed every step
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SimulationConfig {
     #[serde(default = "autoplay")]
@@ -28,6 +38,23 @@ pub struct SimulationConfig {
     pub seed: Option<String>,
     #[serde(default = "mode")]
     pub mode: SimMode,
+    #[serde(default = "schedule")]
+    pub schedule: HeadSchedule,
+    #[serde(default = "head_speeds")]
+    pub head_speeds: Vec<u64>,
+    #[serde(default = "highway_reaction")]
+    pub highway_reaction: bool,
+    #[serde(default = "detection_memory_mb")]
+    pub detection_memory_mb: usize,
+    #[serde(default = "respawn_stuck_heads")]
+    pub respawn_stuck_heads: bool,
+    #[serde(default = "decay_after")]
+    pub decay_after: u64,
+    #[serde(default = "initial_tape")]
+    pub initial_tape: String,
+    // initial_tape as read by cache_tape, so resets don't go back to the file
+    #[serde(skip)]
+    pub tape: InitialTape,
 }
 
 // Default functions
@@ -39,6 +66,13 @@ fn trail_length() -> usize { 16 }
 fn color_cells() -> bool { true }
 fn seed() -> Option<String> { Some(String::new()) }
 fn mode() -> SimMode { SimMode::Halt }
+fn schedule() -> HeadSchedule { HeadSchedule::Sequential }
+fn head_speeds() -> Vec<u64> { Vec::new() }
+fn highway_reaction() -> bool { false }
+fn detection_memory_mb() -> usize { 64 }
+fn respawn_stuck_heads() -> bool { false }
+fn decay_after() -> u64 { 0 }
+fn initial_tape() -> String { String::new() }
 
 impl Default for SimulationConfig {
     fn default() -> Self {
@@ -51,11 +85,42 @@ impl Default for SimulationConfig {
             color_cells: color_cells(),
             seed: seed(),
             mode: mode(),
+            schedule: schedule(),
+            head_speeds: head_speeds(),
+            highway_reaction: highway_reaction(),
+            detection_memory_mb: detection_memory_mb(),
+            respawn_stuck_heads: respawn_stuck_heads(),
+            decay_after: decay_after(),
+            initial_tape: initial_tape(),
+            tape: InitialTape::Blank,
         }
     }
 }
 
 impl SimulationConfig {
+    pub fn cache_tape(&mut self) -> Result<(), String> {
+        self.tape = InitialTape::load(&self.initial_tape)
+            .map_err(|e| format!("simulation.initial_tape: {}", e))?;
+        Ok(())
+    }
+
+    // Head i acts every head_speeds[i % len] steps
+    pub fn head_interval(&self, head_index: usize) -> u64 {
+        if self.head_speeds.is_empty() {
+            1
+        } else {
+            self.head_speeds[head_index % self.head_speeds.len()].max(1)
+        }
+    }
+
+    // Steps until every head is back in phase
+    pub fn schedule_period(&self) -> u64 {
+        fn gcd(a: u64, b: u64) -> u64 { if b == 0 { a } else { gcd(b, a % b) } }
+        self.head_speeds.iter()
+            .map(|&k| k.max(1))
+            .fold(1, |acc, k| acc / gcd(acc, k) * k)
+    }
+
     const DIRECTIONS: &'static [&'static str] = &["L", "R", "U", "D", "N", "S", "W", "E"];
 
     // Random rule generation
@@ -224,4 +289,15 @@ mod tests {
         let serialized = toml::to_string(&SimulationConfig::default()).unwrap();
         assert!(serialized.contains("mode = \"halt\""), "{serialized}");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn head_speeds_cycle_over_heads_and_share_a_period() {
+        let config: SimulationConfig = toml::from_str("schedule = \"random\"\nhead_speeds = [1, 2, 3]").unwrap();
+        assert_eq!(config.schedule, HeadSchedule::Random);
+        assert_eq!(config.head_interval(4), 2);
+        assert_eq!(config.schedule_period(), 6);
+        let empty = SimulationConfig::default();
+        assert_eq!(empty.schedule, HeadSchedule::Sequential);
+        assert_eq!((empty.head_interval(7), empty.schedule_period()), (1, 1));
+    }
+}
@@ -1,5 +1,12 @@
 use ratatui::style::Color;
-use crate::config::Config;
+use crate::config::{Config, CompareConfig, Reaction};
+use crate::config::palette::PALETTE_NAMES;
+use crate::config::glyphs;
+use crate::config::display::display_width;
+use crate::machine::rules::{self, parse_movement, parse_weighted_group, split_top_level};
+use crate::machine::sensors::{self, MAX_SENSOR_KEYS};
+use crate::machine::tape::InitialTape;
+use crate::machine::golly;
 
 pub fn validate_config(config: &Config) -> Result<(), Vec<String>> {
     let mut errors = Vec::new();
@@ -28,6 +35,17 @@ pub fn validate_config(config: &Config) -> Result<(), Vec<String>> {
         errors.push("display: only one of direction_based_chars or randomize_trails can be true".to_string());
     }
 
+    if let Err(e) = glyphs::validate(&config.display.direction_chars) {
+        errors.push(format!("display.direction_chars: {}", e));
+    }
+
+    for (i, glyph) in config.display.state_chars.iter().enumerate() {
+        let width = display_width(glyph);
+        if width == 0 || width > 2 {
+            errors.push(format!("display.state_chars[{}]: must be one or two columns wide", i));
+        }
+    }
+
     // Validate colors
     for (i, color) in config.display.colors.iter().enumerate() {
         if let Err(e) = validate_color(color) {
@@ -35,17 +53,76 @@ pub fn validate_config(config: &Config) -> Result<(), Vec<String>> {
         }
     }
 
-    // Validate fade_trail_color if not empty
-    if !config.display.fade_trail_color.is_empty()
+    if !PALETTE_NAMES.contains(&config.display.palette.as_str()) {
+        errors.push(format!("display.palette: must be one of {}", PALETTE_NAMES.join(", ")));
+    }
+    if config.display.palette == "gradient" && config.display.colors.len() < 2 {
+        errors.push("display.colors: a gradient palette needs at least two colors".to_string());
+    }
+
+    // Validate fade_trail_color if not empty, "auto" follows the terminal background
+    if !config.display.fade_trail_color.is_empty() && config.display.fade_trail_color != "auto"
         && let Err(e) = validate_color(&config.display.fade_trail_color) {
             errors.push(format!("display.fade_trail_color: {}", e));
         }
 
+    if config.display.cell_age_color != "auto"
+        && let Err(e) = validate_color(&config.display.cell_age_color)
+    {
+        errors.push(format!("display.cell_age_color: {}", e));
+    }
+
+    if config.display.heatmap_gradient.len() < 2 {
+        errors.push("display.heatmap_gradient: needs at least two colors".to_string());
+    }
+    for (i, color) in config.display.heatmap_gradient.iter().enumerate() {
+        if let Err(e) = validate_color(color) {
+            errors.push(format!("display.heatmap_gradient[{}]: {}", i, e));
+        }
+    }
+
     // Validate numeric ranges
     if config.simulation.heads == 0 || config.simulation.heads > 256 {
         errors.push("simulation.heads: must be between 1 and 256".to_string());
     }
 
+    if config.simulation.head_speeds.iter().any(|&k| k == 0 || k > 64) {
+        errors.push("simulation.head_speeds: every entry must be between 1 and 64".to_string());
+    }
+
+    if let Err(e) = InitialTape::load(&config.simulation.initial_tape) {
+        errors.push(format!("simulation.initial_tape: {}", e));
+    }
+
+    if config.compare.universes == 0 || config.compare.universes > CompareConfig::MAX_UNIVERSES {
+        errors.push(format!("compare.universes: must be between 1 and {}", CompareConfig::MAX_UNIVERSES));
+    }
+
+    for (i, rule) in config.playlist.rules.iter().enumerate() {
+        if rule.is_empty() {
+            errors.push(format!("playlist.rules[{}]: cannot be empty", i));
+        } else if let Err(e) = validate_rule_string(rule) {
+            errors.push(format!("playlist.rules[{}]: {}", i, e));
+        }
+    }
+
+    // Entries are tried against the config without its own playlist, or applying one would validate the next
+    let mut base = config.clone();
+    base.playlist.entries.clear();
+    for (i, entry) in config.playlist.entries.iter().enumerate() {
+        if let Err(e) = entry.apply(&base) {
+            errors.push(format!("playlist.entries[{}]: {}", i, e));
+        }
+    }
+    let seconds = std::iter::once(config.playlist.seconds).chain(config.playlist.entries.iter().filter_map(|e| e.seconds));
+    if seconds.into_iter().any(|s| !s.is_finite() || s < 0.0) {
+        errors.push("playlist.seconds: must be 0 or more".to_string());
+    }
+
+    if config.reactions.uses(Reaction::NextRule) && config.playlist.rules.is_empty() {
+        errors.push("reactions: next_rule needs at least one rule in playlist.rules".to_string());
+    }
+
     if config.simulation.speed_ms <= 0.0 {
         errors.push("simulation.speed_ms: must be positive".to_string());
     }
@@ -74,6 +151,21 @@ pub fn validate_config(config: &Config) -> Result<(), Vec<String>> {
         ("randomize_seed", &config.controls.randomize_seed),
         ("randomize_rule", &config.controls.randomize_rule),
         ("step", &config.controls.step),
+        ("split", &config.controls.split),
+        ("diff", &config.controls.diff),
+        ("stats", &config.controls.stats),
+        ("heatmap", &config.controls.heatmap),
+        ("mouse", &config.controls.mouse),
+        ("paint_state", &config.controls.paint_state),
+        ("head_direction", &config.controls.head_direction),
+        ("presets", &config.controls.presets),
+        ("favourite", &config.controls.favourite),
+        ("history", &config.controls.history),
+        ("history_back", &config.controls.history_back),
+        ("history_forward", &config.controls.history_forward),
+        ("playlist_next", &config.controls.playlist_next),
+        ("playlist_previous", &config.controls.playlist_previous),
+        ("playlist_pause", &config.controls.playlist_pause),
     ];
 
     for (name, key) in &controls {
@@ -102,6 +194,22 @@ fn validate_rule_string(rule: &str) -> Result<(), String> {
         return Ok(());
     }
 
+    // Handle neighbourhood sensor section
+    let (base, sensor_section) = sensors::split_sensor_section(rule);
+    if let Some(section) = sensor_section {
+        validate_rule_string(base)?;
+        let table = sensors::parse_sensor_table(section)?;
+        let keys = sensors::key_space(&rules::parse_rules(base)?, &table);
+        if keys > MAX_SENSOR_KEYS {
+            return Err(format!("sensor table spans {} keys, the limit is {}", keys, MAX_SENSOR_KEYS));
+        }
+        return Ok(());
+    }
+
+    if golly::is_golly_name(rule) {
+        return golly::to_rule_string(rule).map(|_| ());
+    }
+
     // Handle standard notation
     if rule.trim().starts_with('{') {
         return validate_standard_notation(rule);
@@ -115,7 +223,7 @@ fn validate_rule_string(rule: &str) -> Result<(), String> {
                 return Err("rule combination cannot be empty".to_string());
             }
             
-            let state_parts: Vec<&str> = combo.split(':').collect();
+            let state_parts: Vec<&str> = split_top_level(combo, ':');
             for state_part in state_parts {
                 validate_direction_string(state_part)?;
             }
@@ -124,7 +232,7 @@ fn validate_rule_string(rule: &str) -> Result<(), String> {
     }
 
     // Split by colon for multi-state rules
-    let state_rules: Vec<&str> = rule.split(':').collect();
+    let state_rules: Vec<&str> = split_top_level(rule, ':');
     
     for state_rule in state_rules {
         if state_rule.is_empty() {
@@ -243,18 +351,47 @@ fn validate_direction_string(rule: &str) -> Result<(), String> {
         rule
     };
     
+    // Suffixes go straight after a turn or group: a move first, then ~, each at most once.
+    // Holds whether a move and a ~ have been read, None where no suffix may follow
+    let mut suffixes: Option<(bool, bool)> = None;
     let mut i = 0;
     while i < directions.len() {
         let remaining = &directions[i..];
         
-        if remaining.starts_with("NW") || remaining.starts_with("NE") ||
+        if remaining.starts_with('[') {
+            let Some(end) = remaining.find(']') else {
+                return Err(format!("unclosed '[' in rule '{}'", rule));
+            };
+            parse_weighted_group(&remaining[1..end])?;
+            i += end + 1;
+            suffixes = Some((false, false));
+        } else if remaining.starts_with('(') {
+            let Some(end) = remaining.find(')') else {
+                return Err(format!("unclosed '(' in rule '{}'", rule));
+            };
+            if suffixes != Some((false, false)) {
+                return Err(format!("move '{}' must directly follow a turn in rule '{}'", &remaining[..=end], rule));
+            }
+            parse_movement(&remaining[1..end])?;
+            i += end + 1;
+            suffixes = Some((true, false));
+        } else if remaining.starts_with('~') {
+            match suffixes {
+                Some((moved, false)) => suffixes = Some((moved, true)),
+                _ => return Err(format!("'~' must directly follow a turn or its move in rule '{}'", rule)),
+            }
+            i += 1;
+        } else if remaining.starts_with("NW") || remaining.starts_with("NE") ||
         remaining.starts_with("SW") || remaining.starts_with("SE") {
             i += 2;
+            suffixes = Some((false, false));
         } else if let Some(c) = remaining.chars().next() {
             match c {
-                'L' | 'R' | 'U' | 'D' | 'N' | 'S' | 'E' | 'W' | '0'..='9' => i += 1,
+                'L' | 'R' | 'U' | 'D' | 'N' | 'S' | 'E' | 'W' => suffixes = Some((false, false)),
+                '0'..='9' => suffixes = None,
                 _ => return Err(format!("invalid character '{}' in rule '{}'", c, rule)),
             }
+            i += 1;
         } else {
             break;
         }
@@ -328,3 +465,18 @@ pub fn parse_color(color_str: &str) -> Color {
     // Fallback to white for invalid colors
     Color::White
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffixes_only_follow_a_turn() {
+        for rule in ["R(0)L(2)~D(2+1)", "[R:1|L:3](2)~L", "L(3)1>1,R~>0", "NW(2)SE~"] {
+            assert_eq!(validate_rule_string(rule), Ok(()), "{}", rule);
+        }
+        for rule in ["(2)RL", "~~", "R~~", "~R", "R(2)(2)", "R~(2)", "L1(2)>1,R0>0"] {
+            assert!(validate_rule_string(rule).is_err(), "{}", rule);
+        }
+    }
+}
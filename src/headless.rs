@@ -0,0 +1,138 @@
+use std::{error::Error, fs::File, io::Write, path::PathBuf};
+use crate::config::Config;
+use crate::machine::TuringMachine;
+
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    pub steps: u64,
+    pub width: i32,
+    pub height: i32,
+    pub csv: Option<PathBuf>,
+    pub csv_every: u64,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            steps: 10_000,
+            width: 200,
+            height: 100,
+            csv: None,
+            csv_every: 100,
+        }
+    }
+}
+
+// Runs without a terminal until the step limit or the first outcome, then prints a summary.
+// A preset's seed and rule are run as given rather than read from state
+pub fn run(config: &Config, options: &HeadlessOptions, preset: Option<(Option<&str>, &str)>) -> Result<(), Box<dyn Error>> {
+    let mut machine = TuringMachine::new(config.simulation.heads, &config.simulation.rule, config);
+    if let Some((seed, rule)) = preset {
+        machine.pin(seed, rule);
+    }
+    machine.update_grid_dimensions(options.width, options.height, config);
+    machine.reset_clean(config);
+    if let Some(error) = &machine.rule_error {
+        eprintln!("error: invalid rule '{}': {}", machine.rule_string, error);
+        std::process::exit(1);
+    }
+    simulate(&mut machine, config, options)?;
+    print!("{}", summary(&machine));
+    Ok(())
+}
+
+// Steps the machine, writing the CSV as it goes, until the step limit or an outcome
+fn simulate(machine: &mut TuringMachine, config: &Config, options: &HeadlessOptions) -> Result<(), Box<dyn Error>> {
+    // The summary reports visited cells, so they're kept from the start
+    machine.stats.track_visits(true, machine.steps);
+    let states = machine.palette_span().states;
+
+    let mut csv = match &options.csv {
+        Some(path) => {
+            let mut file = File::create(path)?;
+            writeln!(file, "{}", machine.stats.csv_header(states))?;
+            Some(file)
+        }
+        None => None,
+    };
+
+    let mut last_row = None;
+    while machine.steps < options.steps {
+        machine.step(options.width, options.height, config);
+        if let Some(file) = csv.as_mut()
+            && machine.steps.is_multiple_of(options.csv_every.max(1))
+        {
+            writeln!(file, "{}", machine.stats.csv_row(machine.steps, states))?;
+            last_row = Some(machine.steps);
+        }
+        if machine.pending_outcome(&config.reactions).is_some() {
+            break;
+        }
+    }
+    if let Some(file) = csv.as_mut()
+        && last_row != Some(machine.steps)
+    {
+        writeln!(file, "{}", machine.stats.csv_row(machine.steps, states))?;
+    }
+
+    machine.wait_for_onset();
+    Ok(())
+}
+
+fn summary(machine: &TuringMachine) -> String {
+    let stats = &machine.stats;
+    format!(
+        "rule: {}\nseed: {}\nsteps: {}\nstatus: {}\nbehaviour: {}\npopulation: {}\nvisited: {}\n",
+        machine.rule_string,
+        machine.current_seed,
+        machine.steps,
+        machine.detector.status(),
+        machine.behaviour.behaviour(),
+        stats.population(),
+        stats.visited()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_reports_the_run() {
+        let config = Config::default();
+        let mut machine = TuringMachine::pinned(1, "seed", "RL", &config);
+        machine.update_grid_dimensions(40, 40, &config);
+        machine.reset_clean(&config);
+        for _ in 0..50 {
+            machine.step(40, 40, &config);
+        }
+        let summary = summary(&machine);
+        assert!(summary.starts_with("rule: RL\nseed: seed\nsteps: 50\nstatus: Running\n"), "{}", summary);
+        assert!(summary.contains(&format!("population: {}\n", machine.stats.population())));
+    }
+
+    #[test]
+    fn csv_has_a_row_per_interval_and_one_for_the_last_step() {
+        let path = std::env::temp_dir().join(format!("trmt-headless-{}.csv", std::process::id()));
+        let options = HeadlessOptions {
+            steps: 250,
+            width: 60,
+            height: 40,
+            csv: Some(path.clone()),
+            csv_every: 100,
+        };
+        let config = Config::default();
+        let mut machine = TuringMachine::pinned(1, "seed", "RL", &config);
+        machine.update_grid_dimensions(options.width, options.height, &config);
+        machine.reset_clean(&config);
+        simulate(&mut machine, &config, &options).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("step,"));
+        let steps: Vec<&str> = lines[1..].iter().filter_map(|line| line.split(',').next()).collect();
+        assert_eq!(steps, ["100", "200", "250"]);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::VecDeque;
+use std::fmt;
+use super::grid::Grid;
+
+// Steps between samples of the populated cell count
+const SAMPLE_INTERVAL: u64 = 64;
+// Samples a classification looks back over
+const WINDOW: usize = 32;
+// Population spread, relative to its peak, still counted as a plateau
+const PLATEAU_SPREAD: f64 = 0.03;
+// Growth per sample may vary this much, relative to its mean, and still count as linear
+const LINEAR_VARIATION: f64 = 0.5;
+
+// Heuristic read on how a run is going, unlike DetectionStatus nothing here is proven
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Behaviour {
+    Warming,        // not enough samples yet
+    LinearGrowth,   // populated cells grow at a steady rate
+    Saturated,      // populated cells hold at a plateau
+    Chaotic,        // no structure in how the population moves
+}
+
+impl fmt::Display for Behaviour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Behaviour::Warming => "warming up",
+            Behaviour::LinearGrowth => "linear growth",
+            Behaviour::Saturated => "saturated",
+            Behaviour::Chaotic => "chaotic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BehaviourTracker {
+    populated: u64,
+    samples: VecDeque<u64>,
+    behaviour: Behaviour,
+}
+
+impl BehaviourTracker {
+    pub fn new() -> Self {
+        Self {
+            populated: 0,
+            samples: VecDeque::with_capacity(WINDOW),
+            behaviour: Behaviour::Warming,
+        }
+    }
+
+    pub fn reset(&mut self, grid: &Grid) {
+        self.populated = grid.tape.values().filter(|&&c| c != Grid::EMPTY).count() as u64;
+        self.samples.clear();
+        self.behaviour = Behaviour::Warming;
+    }
+
+    #[inline]
+    pub fn cell_delta(&mut self, old: char, new: char) {
+        match (old == Grid::EMPTY, new == Grid::EMPTY) {
+            (true, false) => self.populated += 1,
+            (false, true) => self.populated = self.populated.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    pub fn on_step_end(&mut self, steps: u64) {
+        if !steps.is_multiple_of(SAMPLE_INTERVAL) {
+            return;
+        }
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(self.populated);
+        self.behaviour = classify(&self.samples);
+    }
+
+    pub fn behaviour(&self) -> Behaviour {
+        self.behaviour
+    }
+}
+
+fn classify(samples: &VecDeque<u64>) -> Behaviour {
+    if samples.len() < WINDOW {
+        return Behaviour::Warming;
+    }
+
+    let max = samples.iter().copied().max().unwrap_or(0) as f64;
+    let min = samples.iter().copied().min().unwrap_or(0) as f64;
+    if max - min <= (max * PLATEAU_SPREAD).max(1.0) {
+        return Behaviour::Saturated;
+    }
+
+    let growth: Vec<f64> = samples.iter().zip(samples.iter().skip(1))
+        .map(|(&a, &b)| b as f64 - a as f64)
+        .collect();
+    let mean = growth.iter().sum::<f64>() / growth.len() as f64;
+    if mean <= 0.0 {
+        return Behaviour::Chaotic;
+    }
+    let variance = growth.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / growth.len() as f64;
+    if variance.sqrt() / mean <= LINEAR_VARIATION {
+        Behaviour::LinearGrowth
+    } else {
+        Behaviour::Chaotic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(f: impl Fn(u64) -> u64) -> VecDeque<u64> {
+        (0..WINDOW as u64).map(f).collect()
+    }
+
+    #[test]
+    fn classifies_sample_windows() {
+        assert_eq!(classify(&window(|i| 10 + 8 * i + i % 2)), Behaviour::LinearGrowth);
+        assert_eq!(classify(&window(|i| 500 + i % 3)), Behaviour::Saturated);
+        assert_eq!(classify(&window(|i| 100 + (i * 37 % 11) * 9)), Behaviour::Chaotic);
+        assert_eq!(classify(&(0..4).collect()), Behaviour::Warming);
+    }
+
+    #[test]
+    fn population_follows_writes() {
+        let mut tracker = BehaviourTracker::new();
+        tracker.cell_delta(Grid::EMPTY, 'B');
+        tracker.cell_delta('B', 'C');
+        tracker.cell_delta(Grid::EMPTY, 'B');
+        tracker.cell_delta('B', Grid::EMPTY);
+        assert_eq!(tracker.populated, 1);
+    }
+}
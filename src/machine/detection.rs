@@ -1,3 +1,4 @@
+use std::fmt;
 use rustc_hash::FxHashMap;
 use super::grid::Grid;
 use super::heads::Head;
@@ -7,9 +8,48 @@ use super::rules::Direction;
 pub enum DetectionStatus {
     Running,
     Stalled { at_step: u64 },
-    Cycle { at_step: u64, period: u64 },
+    // The cycle runs from step `mu` on, the steps before it are the transient. None while it's measured
+    Cycle { at_step: u64, period: u64, mu: Option<u64> },
+    // Repeats around the heads while the whole pattern travels by `displacement`
+    Highway { period: u64, displacement: (i32, i32) },
 }
 
+impl fmt::Display for DetectionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DetectionStatus::Running => write!(f, "Running"),
+            DetectionStatus::Stalled { at_step } => write!(f, "Stalled at step {}", group_digits(at_step)),
+            DetectionStatus::Cycle { period, mu: Some(mu), .. } => {
+                write!(f, "Cycle: transient {} then period {}", group_digits(mu), group_digits(period))
+            }
+            DetectionStatus::Cycle { period, mu: None, .. } => write!(f, "Cycle: period {}", group_digits(period)),
+            DetectionStatus::Highway { period, displacement: (dx, dy) } => {
+                write!(f, "Highway: period {} moving ({}, {})", group_digits(period), dx, dy)
+            }
+        }
+    }
+}
+
+// 11233 -> "11,233"
+pub fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+// Radius of the window read around each head for highway matching
+const HIGHWAY_RADIUS: i32 = 5;
+// Checkpoints stop spacing out here, so longer highway periods go unnoticed
+const HIGHWAY_MAX_PERIOD: u64 = 4096;
+// Consecutive translated repeats needed before a highway is reported
+const HIGHWAY_CONFIRMATIONS: u32 = 3;
+
 // splitmix64 finalizer
 #[inline]
 fn mix64(mut z: u64) -> u64 {
@@ -48,24 +88,229 @@ fn head_contrib(index: usize, x: i32, y: i32, dir: Direction, istate: usize) ->
     mix64(word ^ 0xDEAD_BEEF_CAFE_F00D)
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// Cell ages enter the hash as contrib * G^written_at, scaled by G^-now so the sum
+// only depends on how old each cell is. Odd, so it has an inverse mod 2^64
+const AGE_BASE: u64 = 0x9E37_79B9_7F4A_7C15;
+const AGE_BASE_INV: u64 = inverse(AGE_BASE);
+
+// Newton iteration, each round doubles the correct low bits
+const fn inverse(odd: u64) -> u64 {
+    let mut inv = odd;
+    let mut round = 0;
+    while round < 6 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(odd.wrapping_mul(inv)));
+        round += 1;
+    }
+    inv
+}
+
+fn power(mut base: u64, mut exp: u64) -> u64 {
+    let mut result = 1u64;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.wrapping_mul(base);
+        }
+        base = base.wrapping_mul(base);
+        exp >>= 1;
+    }
+    result
+}
+
+#[inline]
+fn age_contrib(x: i32, y: i32, c: char, written_at: Option<u64>) -> u64 {
+    match written_at {
+        Some(step) if c != Grid::EMPTY => {
+            mix64(cell_contrib(x, y, c) ^ 0xA9E5_0F0F_1234_5678).wrapping_mul(power(AGE_BASE, step))
+        }
+        _ => 0,
+    }
+}
+
+// Non-empty cells sorted by position, much smaller than a hash map of the same cells
+#[derive(Debug, Clone, PartialEq, Default)]
 struct Snapshot {
-    tape: FxHashMap<(i32, i32), char>,
+    tape: Vec<((i32, i32), char)>,
+    // Age of each tape cell, only while cells decay
+    ages: Vec<u64>,
     heads: Vec<(i32, i32, Direction, usize)>,
+    phase: u64,
 }
 
 impl Snapshot {
-    fn capture(grid: &Grid, heads: &[Head]) -> Self {
+    const CELL_BYTES: usize = std::mem::size_of::<((i32, i32), char)>();
+
+    fn capture(grid: &Grid, heads: &[Head], phase: u64, clock: Option<u64>) -> Self {
+        let mut tape: Vec<((i32, i32), char)> = grid.tape.iter()
+            .filter(|&(_, &c)| c != Grid::EMPTY)
+            .map(|(&pos, &c)| (pos, c))
+            .collect();
+        tape.sort_unstable_by_key(|&(pos, _)| pos);
+        let ages = match clock {
+            Some(now) => tape.iter().map(|&((x, y), _)| grid.age(x, y, now).unwrap_or(u64::MAX)).collect(),
+            None => Vec::new(),
+        };
+        Self { tape, ages, ..Self::heads_only(heads, phase) }
+    }
+
+    // Checkpoint over the memory budget, its tape is checked through the cells changed since
+    fn heads_only(heads: &[Head], phase: u64) -> Self {
         Self {
-            tape: grid.tape.iter()
-                .filter(|&(_, &c)| c != Grid::EMPTY)
-                .map(|(&pos, &c)| (pos, c))
-                .collect(),
+            tape: Vec::new(),
+            ages: Vec::new(),
             heads: heads.iter()
                 .map(|h| (h.x, h.y, h.direction, h.internal_state))
                 .collect(),
+            phase,
+        }
+    }
+
+    fn heads_match(&self, heads: &[Head], phase: u64) -> bool {
+        self.phase == phase
+            && self.heads.len() == heads.len()
+            && self.heads.iter().zip(heads).all(|(&(x, y, direction, state), h)| {
+                (x, y, direction, state) == (h.x, h.y, h.direction, h.internal_state)
+            })
+    }
+
+    // Compares against the live tape without building a second snapshot
+    fn matches(&self, grid: &Grid, heads: &[Head], phase: u64, clock: Option<u64>) -> bool {
+        self.heads_match(heads, phase)
+            && grid.tape.values().filter(|&&c| c != Grid::EMPTY).count() == self.tape.len()
+            && self.tape.iter().all(|&((x, y), c)| grid.get_cell(x, y) == c)
+            && clock.is_none_or(|now| {
+                self.tape.iter().zip(&self.ages).all(|(&((x, y), _), &age)| grid.age(x, y, now) == Some(age))
+            })
+    }
+}
+
+#[inline]
+fn wrap(value: i32, size: i32) -> i32 {
+    if size > 0 { value.rem_euclid(size) } else { value }
+}
+
+// Shortest offset on a torus axis
+#[inline]
+pub fn offset(from: i32, to: i32, size: i32) -> i32 {
+    let delta = to - from;
+    if size <= 0 {
+        return delta;
+    }
+    let delta = delta.rem_euclid(size);
+    if delta > size / 2 { delta - size } else { delta }
+}
+
+// Tape around every head and the heads themselves, relative to the first head
+#[derive(Debug, Clone, PartialEq, Default)]
+struct LocalView {
+    heads: Vec<(i32, i32, Direction, usize)>,
+    cells: Vec<char>,
+    phase: u64,
+}
+
+impl LocalView {
+    fn capture(grid: &Grid, heads: &[Head], phase: u64, bounds: (i32, i32)) -> Self {
+        let Some(first) = heads.first() else {
+            return Self { phase, ..Default::default() };
+        };
+        let (width, height) = bounds;
+        let mut cells = Vec::with_capacity(heads.len() * ((2 * HIGHWAY_RADIUS + 1) as usize).pow(2));
+        for head in heads {
+            for dy in -HIGHWAY_RADIUS..=HIGHWAY_RADIUS {
+                for dx in -HIGHWAY_RADIUS..=HIGHWAY_RADIUS {
+                    cells.push(grid.get_cell(wrap(head.x + dx, width), wrap(head.y + dy, height)));
+                }
+            }
+        }
+        Self {
+            heads: heads.iter()
+                .map(|h| (offset(first.x, h.x, width), offset(first.y, h.y, height), h.direction, h.internal_state))
+                .collect(),
+            cells,
+            phase,
         }
     }
+
+    // Cheap pre-check so the window is only read when the heads line up
+    fn heads_match(&self, heads: &[Head], phase: u64, bounds: (i32, i32)) -> bool {
+        let Some(first) = heads.first() else {
+            return false;
+        };
+        self.phase == phase
+            && self.heads.len() == heads.len()
+            && self.heads.iter().zip(heads).all(|(&(x, y, direction, state), h)| {
+                x == offset(first.x, h.x, bounds.0)
+                    && y == offset(first.y, h.y, bounds.1)
+                    && direction == h.direction
+                    && state == h.internal_state
+            })
+    }
+}
+
+// Brent-style search over local views, with a capped checkpoint spacing
+#[derive(Debug, Clone, Default)]
+struct HighwaySearch {
+    view: LocalView,
+    origin: (i32, i32),
+    power: u64,
+    lam: u64,
+    // Period, displacement and repeats seen so far
+    candidate: Option<(u64, (i32, i32), u32)>,
+}
+
+impl HighwaySearch {
+    fn reset(&mut self, grid: &Grid, heads: &[Head], bounds: (i32, i32)) {
+        *self = Self { power: 1, ..Default::default() };
+        self.checkpoint(grid, heads, 0, bounds);
+    }
+
+    fn checkpoint(&mut self, grid: &Grid, heads: &[Head], phase: u64, bounds: (i32, i32)) {
+        self.view = LocalView::capture(grid, heads, phase, bounds);
+        self.origin = heads.first().map_or((0, 0), |h| (h.x, h.y));
+        self.lam = 0;
+    }
+
+    fn displacement(&self, heads: &[Head], bounds: (i32, i32)) -> (i32, i32) {
+        heads.first().map_or((0, 0), |h| {
+            (offset(self.origin.0, h.x, bounds.0), offset(self.origin.1, h.y, bounds.1))
+        })
+    }
+
+    fn matches(&self, grid: &Grid, heads: &[Head], phase: u64, bounds: (i32, i32)) -> bool {
+        self.view.heads_match(heads, phase, bounds) && self.view == LocalView::capture(grid, heads, phase, bounds)
+    }
+
+    fn on_step_end(&mut self, grid: &Grid, heads: &[Head], phase: u64, bounds: (i32, i32)) -> Option<(u64, (i32, i32))> {
+        self.lam += 1;
+        match self.candidate {
+            Some((period, displacement, seen)) => {
+                if self.lam < period {
+                    return None;
+                }
+                let confirmed = self.displacement(heads, bounds) == displacement
+                    && self.matches(grid, heads, phase, bounds);
+                self.candidate = confirmed.then_some((period, displacement, seen + 1));
+                if !confirmed {
+                    self.power = 1;
+                }
+                self.checkpoint(grid, heads, phase, bounds);
+                if confirmed && seen + 1 >= HIGHWAY_CONFIRMATIONS {
+                    return Some((period, displacement));
+                }
+            }
+            None => {
+                // Repeats in place are left to the exact cycle check
+                let displacement = self.displacement(heads, bounds);
+                if displacement != (0, 0) && self.matches(grid, heads, phase, bounds) {
+                    self.candidate = Some((self.lam, displacement, 1));
+                    self.checkpoint(grid, heads, phase, bounds);
+                } else if self.lam == self.power {
+                    self.power = (self.power << 1).min(HIGHWAY_MAX_PERIOD);
+                    self.checkpoint(grid, heads, phase, bounds);
+                }
+            }
+        }
+        None
+    }
 }
 
 fn full_hash(grid: &Grid, heads: &[Head]) -> u64 {
@@ -79,40 +324,129 @@ fn full_hash(grid: &Grid, heads: &[Head]) -> u64 {
     h
 }
 
-#[derive(Debug)]
+fn full_age_hash(grid: &Grid) -> u64 {
+    grid.written_at.iter().fold(0u64, |h, (&(x, y), &step)| {
+        h.wrapping_add(age_contrib(x, y, grid.get_cell(x, y), Some(step)))
+    })
+}
+
+// Structural equality of two machine states, used to confirm hash matches.
+// Clocks are the step counts of both, given when cell ages are part of the state
+pub fn same_state(grid: &Grid, heads: &[Head], other_grid: &Grid, other_heads: &[Head], clocks: Option<(u64, u64)>) -> bool {
+    heads.len() == other_heads.len()
+        && Snapshot::capture(grid, heads, 0, clocks.map(|c| c.0)) == Snapshot::capture(other_grid, other_heads, 0, clocks.map(|c| c.1))
+}
+
+// What a checkpoint keeps of the tape
+#[derive(Debug, Clone)]
+enum CheckpointTape {
+    // All of it, in the snapshot
+    Stored,
+    // Over the memory budget: the cells changed since, with what they held then
+    Changes(FxHashMap<(i32, i32), (char, Option<u64>)>),
+    // More changes than fit the budget either, so matches can't be proven
+    Lost,
+}
+
+impl CheckpointTape {
+    const CHANGE_BYTES: usize = std::mem::size_of::<((i32, i32), (char, Option<u64>))>();
+}
+
+#[derive(Debug, Clone)]
 pub struct CycleDetector {
     hash: u64,
+    // Sum of age_contrib over aged cells, zero unless cells decay
+    age_hash: u64,
+    decaying: bool,
+    clock: u64,
     saved_hash: u64,
     snapshot: Snapshot,
+    tape: CheckpointTape,
+    checkpoint_clock: u64,
+    // Non-blank cells on the live tape, which a stored checkpoint would copy
+    cells: usize,
+    memory_budget: usize,
     power: u64,
     lam: u64,
     status: DetectionStatus,
+    cycles_enabled: bool,
+    highways_enabled: bool,
+    // Head speeds make the step count part of the state, modulo this
+    phase_period: u64,
+    highways: HighwaySearch,
+    highway: Option<(u64, (i32, i32))>,
+    bounds: (i32, i32),
 }
 
 impl CycleDetector {
     pub fn new() -> Self {
         Self {
             hash: 0,
+            age_hash: 0,
+            decaying: false,
+            clock: 0,
             saved_hash: 0,
-            snapshot: Snapshot { tape: FxHashMap::default(), heads: Vec::new() },
+            snapshot: Snapshot::default(),
+            tape: CheckpointTape::Stored,
+            checkpoint_clock: 0,
+            cells: 0,
+            memory_budget: usize::MAX,
             power: 1,
             lam: 0,
             status: DetectionStatus::Running,
+            cycles_enabled: true,
+            highways_enabled: true,
+            phase_period: 1,
+            highways: HighwaySearch::default(),
+            highway: None,
+            bounds: (0, 0),
         }
     }
 
-    pub fn reset_with(&mut self, grid: &Grid, heads: &[Head]) {
+    pub fn reset_with(&mut self, grid: &Grid, heads: &[Head], steps: u64) {
         self.hash = full_hash(grid, heads);
-        self.saved_hash = self.hash;
-        self.snapshot = Snapshot::capture(grid, heads);
+        self.age_hash = if self.decaying { full_age_hash(grid) } else { 0 };
+        self.clock = steps;
+        self.cells = grid.tape.values().filter(|&&c| c != Grid::EMPTY).count();
+        self.checkpoint(grid, heads, 0);
         self.power = 1;
         self.lam = 0;
         self.status = DetectionStatus::Running;
+        self.highways.reset(grid, heads, self.bounds);
+        self.highway = None;
     }
 
     #[inline]
     pub fn cell_delta(&mut self, x: i32, y: i32, old: char, new: char) {
         self.hash ^= cell_contrib(x, y, old) ^ cell_contrib(x, y, new);
+        match (old == Grid::EMPTY, new == Grid::EMPTY) {
+            (true, false) => self.cells += 1,
+            (false, true) => self.cells = self.cells.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    // A cell was written, with its state and last write before and after. Ages only
+    // count while cells decay
+    #[inline]
+    pub fn write_delta(&mut self, x: i32, y: i32, old: (char, Option<u64>), new: (char, Option<u64>)) {
+        if self.decaying {
+            self.age_hash = self.age_hash
+                .wrapping_sub(age_contrib(x, y, old.0, old.1))
+                .wrapping_add(age_contrib(x, y, new.0, new.1));
+        }
+        if old.0 == new.0 && (!self.decaying || old.1 == new.1) {
+            return;
+        }
+        if let CheckpointTape::Changes(changes) = &mut self.tape
+            && !changes.contains_key(&(x, y))
+        {
+            if (changes.len() + 1).saturating_mul(CheckpointTape::CHANGE_BYTES) > self.memory_budget {
+                self.tape = CheckpointTape::Lost;
+            } else {
+                changes.insert((x, y), old);
+            }
+        }
     }
 
     #[inline]
@@ -132,26 +466,116 @@ impl CycleDetector {
         }
     }
 
+    // Stalls stay provable either way, only cycle checks are switched
+    pub fn set_cycle_detection(&mut self, enabled: bool) {
+        self.cycles_enabled = enabled;
+    }
+
+    // Highways only need each head to be deterministic, so random head order and respawns keep them
+    pub fn set_highway_detection(&mut self, enabled: bool) {
+        self.highways_enabled = enabled;
+    }
+
+    // Decaying cells make their ages part of the state
+    pub fn set_decay(&mut self, enabled: bool) {
+        self.decaying = enabled;
+    }
+
+    fn aged_clock(&self) -> Option<u64> {
+        self.decaying.then_some(self.clock)
+    }
+
+    pub fn set_phase_period(&mut self, period: u64) {
+        self.phase_period = period.max(1);
+    }
+
+    // Bytes a checkpoint tape may take before checkpoints only track the cells changed since
+    pub fn set_memory_budget(&mut self, bytes: usize) {
+        self.memory_budget = bytes;
+    }
+
+    fn checkpoint(&mut self, grid: &Grid, heads: &[Head], phase: u64) {
+        self.saved_hash = self.fingerprint();
+        self.checkpoint_clock = self.clock;
+        if self.cells.saturating_mul(Snapshot::CELL_BYTES) <= self.memory_budget {
+            self.snapshot = Snapshot::capture(grid, heads, phase, self.aged_clock());
+            self.tape = CheckpointTape::Stored;
+        } else {
+            self.snapshot = Snapshot::heads_only(heads, phase);
+            self.tape = CheckpointTape::Changes(FxHashMap::default());
+        }
+    }
+
+    // The live state equals the checkpoint's, which only a structural match proves
+    fn matches_checkpoint(&self, grid: &Grid, heads: &[Head], phase: u64) -> bool {
+        match &self.tape {
+            CheckpointTape::Stored => self.snapshot.matches(grid, heads, phase, self.aged_clock()),
+            CheckpointTape::Changes(changes) => {
+                let (then, now) = (self.checkpoint_clock, self.clock);
+                self.snapshot.heads_match(heads, phase)
+                    && changes.iter().all(|(&(x, y), &(state, written))| {
+                        grid.get_cell(x, y) == state
+                            && (!self.decaying || state == Grid::EMPTY
+                                || written.map(|step| then.saturating_sub(step)) == grid.age(x, y, now))
+                    })
+                    // Aged cells left alone have grown older since
+                    && (!self.decaying || grid.written_at.keys().all(|pos| changes.contains_key(pos)))
+            }
+            CheckpointTape::Lost => false,
+        }
+    }
+
+    // Torus size, so highway offsets are measured across the wrap
+    pub fn set_bounds(&mut self, width: i32, height: i32) {
+        self.bounds = (width, height);
+    }
+
     pub fn on_step_end(&mut self, grid: &Grid, heads: &[Head], steps: u64) {
+        self.clock = steps;
         if self.status != DetectionStatus::Running {
             return;
         }
+        let phase = steps % self.phase_period;
+        // Highways are watched for even when exact cycles can't be proven
+        if self.highways_enabled && self.highway.is_none() {
+            self.highway = self.highways.on_step_end(grid, heads, phase, self.bounds);
+        }
+        if !self.cycles_enabled {
+            return;
+        }
         self.lam += 1;
-        // Only a structural match proves a cycle
-        if self.hash == self.saved_hash && self.snapshot == Snapshot::capture(grid, heads) {
-            self.status = DetectionStatus::Cycle { at_step: steps, period: self.lam };
+        if self.fingerprint() == self.saved_hash && self.matches_checkpoint(grid, heads, phase) {
+            self.status = DetectionStatus::Cycle { at_step: steps, period: self.lam, mu: None };
             return;
         }
         if self.lam == self.power {
             self.power <<= 1;
             self.lam = 0;
-            self.saved_hash = self.hash;
-            self.snapshot = Snapshot::capture(grid, heads);
+            self.checkpoint(grid, heads, phase);
+        }
+    }
+
+    // Filled in by the machine once it has replayed the run to find the cycle start
+    pub fn record_onset(&mut self, onset: u64) {
+        if let DetectionStatus::Cycle { ref mut mu, .. } = self.status {
+            *mu = Some(onset);
         }
     }
 
+    pub fn fingerprint(&self) -> u64 {
+        if self.decaying {
+            self.hash ^ self.age_hash.wrapping_mul(power(AGE_BASE_INV, self.clock))
+        } else {
+            self.hash
+        }
+    }
+
+    // A proven stall or cycle outranks a highway, which keeps being watched for one
     pub fn status(&self) -> DetectionStatus {
-        self.status
+        match (self.status, self.highway) {
+            (DetectionStatus::Running, Some((period, displacement))) => DetectionStatus::Highway { period, displacement },
+            (status, _) => status,
+        }
     }
 }
 
@@ -169,7 +593,7 @@ mod tests {
         let mut grid = Grid::new();
         let mut heads = vec![make_head(3, 4)];
         let mut det = CycleDetector::new();
-        det.reset_with(&grid, &heads);
+        det.reset_with(&grid, &heads, 0);
 
         let writes = [(1, 2, 'B'), (5, 5, 'C'), (1, 2, 'D'), (5, 5, 'A')];
         for &(x, y, new) in &writes {
@@ -192,7 +616,7 @@ mod tests {
         let grid = Grid::new();
         let mut heads = vec![make_head(0, 0)];
         let mut det = CycleDetector::new();
-        det.reset_with(&grid, &heads);
+        det.reset_with(&grid, &heads, 0);
 
         let orbit = [
             (1, 0, Direction::Right),
@@ -223,7 +647,7 @@ mod tests {
         let grid = Grid::new();
         let heads = vec![make_head(0, 0)];
         let mut det = CycleDetector::new();
-        det.reset_with(&grid, &heads);
+        det.reset_with(&grid, &heads, 0);
         // Forge a collision, same hash but different structure
         det.snapshot.heads[0].0 = 99;
         det.on_step_end(&grid, &heads, 1);
@@ -237,7 +661,7 @@ mod tests {
         let mut det = CycleDetector::new();
         det.mark_stalled(10);
         grid.set_cell(1, 1, 'B', Color::White, None, false);
-        det.reset_with(&grid, &heads);
+        det.reset_with(&grid, &heads, 0);
         assert_eq!(det.status(), DetectionStatus::Running);
         assert_eq!(det.hash, full_hash(&grid, &heads));
     }
@@ -247,9 +671,110 @@ mod tests {
         let grid = Grid::new();
         let heads = vec![make_head(0, 0)];
         let mut det = CycleDetector::new();
-        det.reset_with(&grid, &heads);
+        det.reset_with(&grid, &heads, 0);
         det.mark_stalled(5);
         det.on_step_end(&grid, &heads, 6);
         assert_eq!(det.status(), DetectionStatus::Stalled { at_step: 5 });
     }
+
+    #[test]
+    fn torus_offsets_take_the_short_way() {
+        assert_eq!(offset(1, 9, 10), -2);
+        assert_eq!(offset(9, 1, 10), 2);
+        assert_eq!(offset(3, 5, 0), 2);
+    }
+
+    #[test]
+    fn translated_walk_is_a_highway_not_a_cycle() {
+        // A head marching right over blank tape, the simplest highway
+        let grid = Grid::new();
+        let mut heads = vec![make_head(0, 0)];
+        heads[0].direction = Direction::Right;
+        let mut det = CycleDetector::new();
+        det.reset_with(&grid, &heads, 0);
+        for step in 1..=20u64 {
+            heads[0].x += 1;
+            det.on_step_end(&grid, &heads, step);
+        }
+        assert_eq!(det.status(), DetectionStatus::Highway { period: 1, displacement: (1, 0) });
+
+        // Still found when cycles can't be proven, as with random schedules
+        det.set_cycle_detection(false);
+        det.reset_with(&grid, &heads, 20);
+        for step in 21..=40u64 {
+            heads[0].x += 1;
+            det.on_step_end(&grid, &heads, step);
+        }
+        assert_eq!(det.status(), DetectionStatus::Highway { period: 1, displacement: (1, 0) });
+    }
+
+    #[test]
+    fn status_text_groups_digits() {
+        let cycle = DetectionStatus::Cycle { at_step: 20_000, period: 4, mu: Some(11_233) };
+        assert_eq!(cycle.to_string(), "Cycle: transient 11,233 then period 4");
+        let measuring = DetectionStatus::Cycle { at_step: 20_000, period: 4, mu: None };
+        assert_eq!(measuring.to_string(), "Cycle: period 4");
+        assert_eq!(DetectionStatus::Stalled { at_step: 1_000_000 }.to_string(), "Stalled at step 1,000,000");
+        assert_eq!(group_digits(999), "999");
+    }
+
+    #[test]
+    fn sorted_snapshot_matches_live_tape() {
+        let mut grid = Grid::new();
+        let heads = vec![make_head(1, 1)];
+        for &(x, y, c) in &[(3, 1, 'B'), (-2, 5, 'C'), (0, 0, 'B')] {
+            grid.set_cell(x, y, c, Color::White, None, false);
+        }
+        let snapshot = Snapshot::capture(&grid, &heads, 0, None);
+        assert!(snapshot.tape.is_sorted());
+        assert!(snapshot.matches(&grid, &heads, 0, None));
+        assert!(!snapshot.matches(&grid, &heads, 1, None));
+
+        // Blanked cells count as absent
+        grid.set_cell(0, 0, Grid::EMPTY, Color::White, None, false);
+        assert!(!snapshot.matches(&grid, &heads, 0, None));
+        let snapshot = Snapshot::capture(&grid, &heads, 0, None);
+        grid.set_cell(7, 7, 'B', Color::White, None, false);
+        assert!(!snapshot.matches(&grid, &heads, 0, None));
+    }
+
+    #[test]
+    fn over_budget_checkpoints_check_the_cells_changed_since() {
+        let mut grid = Grid::new();
+        for x in 3..6 {
+            grid.set_cell(x, 5, 'B', Color::White, None, false);
+        }
+        let heads = vec![make_head(0, 0)];
+        let mut det = CycleDetector::new();
+        // Too small for the tape, room for one change
+        det.set_memory_budget(CheckpointTape::CHANGE_BYTES);
+        det.reset_with(&grid, &heads, 0);
+        assert!(det.snapshot.tape.is_empty());
+
+        let write = |det: &mut CycleDetector, grid: &mut Grid, state| {
+            let old = grid.get_cell(5, 5);
+            grid.set_cell(5, 5, state, Color::White, None, false);
+            det.cell_delta(5, 5, old, state);
+            det.write_delta(5, 5, (old, None), (state, None));
+        };
+        // A forged hash match is rejected by the changed cell
+        write(&mut det, &mut grid, 'C');
+        det.saved_hash = det.fingerprint();
+        det.on_step_end(&grid, &heads, 1);
+        assert_eq!(det.status(), DetectionStatus::Running);
+
+        // Changing it back and forth again repeats the step 1 checkpoint
+        write(&mut det, &mut grid, 'B');
+        det.on_step_end(&grid, &heads, 2);
+        write(&mut det, &mut grid, 'C');
+        det.on_step_end(&grid, &heads, 3);
+        assert_eq!(det.status(), DetectionStatus::Cycle { at_step: 3, period: 2, mu: None });
+
+        // More changes than the budget leave nothing to prove a repeat with
+        det.reset_with(&grid, &heads, 3);
+        grid.set_cell(4, 5, 'C', Color::White, None, false);
+        det.write_delta(4, 5, ('B', None), ('C', None));
+        write(&mut det, &mut grid, 'B');
+        assert!(matches!(det.tape, CheckpointTape::Lost));
+    }
 }
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, BTreeSet};
+use super::rules::{Direction, Movement, StateTransition, TurnDirection, state_char, state_index};
+
+// Golly names a turmite by its transition table, one character per value:
+//   Turmite_181181121010            new colour, turn (1 none, 2 right, 4 u-turn, 8 left), new state
+//   AbsoluteTurmite_1N10S11S30N21W0 new colour, heading (N/E/S/W), new state
+// Triples run through every colour of state 0, then state 1 and so on
+const RELATIVE: &str = "Turmite_";
+const ABSOLUTE: &str = "AbsoluteTurmite_";
+
+// One hex digit per value
+const MAX_VALUES: usize = 16;
+
+// Golly's headings, clockwise from north as in its rule tables
+const HEADINGS: [(char, Direction); 4] = [
+    ('N', Direction::Up), ('E', Direction::Right), ('S', Direction::Down), ('W', Direction::Left),
+];
+
+pub fn is_golly_name(rule: &str) -> bool {
+    let rule = rule.trim();
+    rule.starts_with(RELATIVE) || rule.starts_with(ABSOLUTE)
+}
+
+// The same table in trmt's own notation, standard notation for relative turmites
+// and explicit transitions for absolute ones
+pub fn to_rule_string(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let (spec, absolute) = match (name.strip_prefix(ABSOLUTE), name.strip_prefix(RELATIVE)) {
+        (Some(spec), _) => (spec, true),
+        (_, Some(spec)) => (spec, false),
+        _ => return Err(format!("'{}' is not a Golly turmite name", name)),
+    };
+
+    let chars: Vec<char> = spec.chars().collect();
+    if chars.is_empty() || !chars.len().is_multiple_of(3) {
+        return Err(format!("'{}' needs a whole number of colour, turn, state triples", spec));
+    }
+    let mut triples = Vec::new();
+    for triple in chars.chunks(3) {
+        let digit = |c: char| c.to_digit(16).map(|d| d as usize).ok_or_else(|| format!("'{}' is not a digit", c));
+        let (color, state) = (digit(triple[0])?, digit(triple[2])?);
+        let turn = if absolute {
+            HEADINGS.iter().position(|&(letter, _)| letter == triple[1])
+                .ok_or_else(|| format!("'{}' is not a heading, use N, E, S or W", triple[1]))?
+        } else {
+            match digit(triple[1])? {
+                turn @ (1 | 2 | 4 | 8) => turn,
+                turn => return Err(format!("turn {} is not supported, use 1, 2, 4 or 8 (turmites that split can't be run)", turn)),
+            }
+        };
+        triples.push((color, turn, state));
+    }
+
+    // Golly leaves the table's shape implicit, take the fewest colours every triple fits
+    let (colors, states) = (1..=triples.len())
+        .filter(|colors| triples.len().is_multiple_of(*colors))
+        .map(|colors| (colors, triples.len() / colors))
+        .find(|&(colors, states)| triples.iter().all(|&(c, _, s)| c < colors && s < states))
+        .ok_or_else(|| "no table shape fits every colour and state in the name".to_string())?;
+
+    let rows: Vec<Vec<(usize, usize, usize)>> = triples.chunks(colors).map(<[_]>::to_vec).collect();
+    debug_assert_eq!(rows.len(), states);
+    if absolute {
+        if colors > 10 {
+            return Err("absolute turmites are limited to 10 colours".to_string());
+        }
+        let state_rules: Vec<String> = rows.iter()
+            .map(|row| row.iter()
+                .map(|&(color, heading, state)| format!("{}{}>{}", HEADINGS[heading].0, color, state))
+                .collect::<Vec<_>>()
+                .join(","))
+            .collect();
+        Ok(state_rules.join(":"))
+    } else {
+        let state_rules: Vec<String> = rows.iter()
+            .map(|row| {
+                let cells: Vec<String> = row.iter().map(|(c, t, s)| format!("{{{}, {}, {}}}", c, t, s)).collect();
+                format!("{{{}}}", cells.join(", "))
+            })
+            .collect();
+        Ok(format!("{{{}}}", state_rules.join(", ")))
+    }
+}
+
+// The rule a Golly .rule file was generated from, read from its @RULE line
+pub fn import_rule_file(text: &str) -> Result<String, String> {
+    let name = text.lines()
+        .find_map(|line| line.trim().strip_prefix("@RULE"))
+        .map(str::trim)
+        .ok_or_else(|| "no @RULE line found".to_string())?;
+    if !is_golly_name(name) {
+        return Err(format!("'{}' is not a turmite, only tables generated from Turmite_ or AbsoluteTurmite_ names can be imported", name));
+    }
+    to_rule_string(name)?;
+    Ok(name.to_string())
+}
+
+// A complete table over the states and colours reachable from state 0 on a blank tape
+struct Table {
+    colors: usize,
+    states: usize,
+    // (new colour, turn, new state) by state, then colour
+    cells: Vec<(usize, TurnDirection, usize)>,
+}
+
+impl Table {
+    fn from_rules(rules: &BTreeMap<(usize, char), StateTransition>) -> Result<Self, String> {
+        let mut states = BTreeSet::from([0]);
+        let mut colors = BTreeSet::from([0]);
+        let mut grown = true;
+        while grown {
+            grown = false;
+            let pairs: Vec<(usize, usize)> = states.iter().flat_map(|&s| colors.iter().map(move |&c| (s, c))).collect();
+            for (state, color) in pairs {
+                let Some(transition) = rules.get(&(state, state_char(color))) else {
+                    return Err(format!("state {} has no transition for colour {}, Golly needs a complete table", state, color));
+                };
+                let written = state_index(transition.new_cell_state).ok_or("unknown cell state")?;
+                grown |= colors.insert(written) | states.insert(transition.new_internal_state);
+            }
+        }
+        let (color_count, state_count) = (colors.len(), states.len());
+        if colors.last() != Some(&(color_count - 1)) || states.last() != Some(&(state_count - 1)) {
+            return Err("Golly tables need colours and states numbered without gaps".to_string());
+        }
+        if color_count > MAX_VALUES || state_count > MAX_VALUES {
+            return Err(format!("Golly names hold at most {} colours and states", MAX_VALUES));
+        }
+
+        let mut cells = Vec::with_capacity(color_count * state_count);
+        for state in 0..state_count {
+            for color in 0..color_count {
+                let transition = &rules[&(state, state_char(color))];
+                if transition.is_stochastic() || transition.movement != Movement::STEP || !transition.writes {
+                    return Err(format!("state {} on colour {} uses weights, jumps or '~', which Golly turmites can't", state, color));
+                }
+                let written = state_index(transition.new_cell_state).unwrap_or(0);
+                cells.push((written, transition.turn_direction, transition.new_internal_state));
+            }
+        }
+        Ok(Self { colors: color_count, states: state_count, cells })
+    }
+
+    fn is_absolute(&self) -> Result<bool, String> {
+        let absolute = self.cells.iter().filter(|(_, turn, _)| matches!(turn, TurnDirection::Absolute(_))).count();
+        match absolute {
+            0 => Ok(false),
+            n if n == self.cells.len() => Ok(true),
+            _ => Err("the rule mixes relative and absolute turns, Golly turmites use one or the other".to_string()),
+        }
+    }
+
+    // Heading after turning, as an index into HEADINGS
+    fn heading(turn: TurnDirection, heading: usize) -> Result<usize, String> {
+        match turn {
+            TurnDirection::None => Ok(heading),
+            TurnDirection::Right => Ok((heading + 1) % 4),
+            TurnDirection::UTurn => Ok((heading + 2) % 4),
+            TurnDirection::Left => Ok((heading + 3) % 4),
+            TurnDirection::Absolute(direction) => HEADINGS.iter().position(|&(_, d)| d == direction)
+                .ok_or_else(|| "Golly turmites can't move diagonally".to_string()),
+        }
+    }
+}
+
+pub fn export_name(rules: &BTreeMap<(usize, char), StateTransition>) -> Result<String, String> {
+    let table = Table::from_rules(rules)?;
+    let absolute = table.is_absolute()?;
+    let mut name = String::from(if absolute { ABSOLUTE } else { RELATIVE });
+    for &(color, turn, state) in &table.cells {
+        let turn = match turn {
+            TurnDirection::None => '1',
+            TurnDirection::Right => '2',
+            TurnDirection::UTurn => '4',
+            TurnDirection::Left => '8',
+            TurnDirection::Absolute(_) => HEADINGS[Table::heading(turn, 0)?].0,
+        };
+        let digit = |value: usize| char::from_digit(value as u32, 16).unwrap_or('0');
+        name.extend([digit(color), turn, digit(state)]);
+    }
+    Ok(name)
+}
+
+// A Golly rule table running the turmite on a von Neumann neighbourhood. Cells hold a colour,
+// or a colour with the turmite's state and heading on it
+pub fn export_rule_table(rules: &BTreeMap<(usize, char), StateTransition>) -> Result<String, String> {
+    let name = export_name(rules)?;
+    let table = Table::from_rules(rules)?;
+    let (colors, states) = (table.colors, table.states);
+    let cell_states = colors + colors * states * 4;
+    if cell_states > 256 {
+        return Err(format!("the table needs {} cell states, Golly allows 256", cell_states));
+    }
+    let turmite = |color: usize, state: usize, heading: usize| colors + (state * 4 + heading) * colors + color;
+
+    let all = (0..cell_states).map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+    let mut text = format!(
+        "@RULE {}\n\n@TABLE\nn_states:{}\nneighborhood:vonNeumann\nsymmetries:none\n\n",
+        name, cell_states,
+    );
+    for var in ["a", "b", "c", "d"] {
+        text.push_str(&format!("var {}={{{}}}\n", var, all));
+    }
+
+    text.push_str("\n# C,N,E,S,W,C'\n# The turmite leaves its new colour behind\n");
+    for state in 0..states {
+        for color in 0..colors {
+            let (written, _, _) = table.cells[state * colors + color];
+            for heading in 0..4 {
+                text.push_str(&format!("{},a,b,c,d,{}\n", turmite(color, state, heading), written));
+            }
+        }
+    }
+
+    // A neighbour at N, E, S or W arrives by heading S, W, N or E
+    text.push_str("# The turmite moves onto the colour beside it\n");
+    for (side, arriving) in [(0, 2), (1, 3), (2, 0), (3, 1)] {
+        for state in 0..states {
+            for color in 0..colors {
+                let (_, turn, next) = table.cells[state * colors + color];
+                for heading in 0..4 {
+                    if Table::heading(turn, heading)? != arriving {
+                        continue;
+                    }
+                    for below in 0..colors {
+                        let mut neighbours = ["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()];
+                        neighbours[side] = turmite(color, state, heading).to_string();
+                        text.push_str(&format!("{},{},{}\n", below, neighbours.join(","), turmite(below, next, arriving)));
+                    }
+                }
+            }
+        }
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::rules::parse_rules;
+
+    const FIBONACCI: &str = "Turmite_181181121010";
+
+    #[test]
+    fn fibonacci_spiral_round_trips() {
+        let rule = to_rule_string(FIBONACCI).unwrap();
+        assert_eq!(rule, "{{{1, 8, 1}, {1, 8, 1}}, {{1, 2, 1}, {0, 1, 0}}}");
+        // The README's explicit spelling of the same turmite exports to the same name
+        assert_eq!(export_name(&parse_rules("L1>1,L1>1:R1>1,D0>0").unwrap()).unwrap(), FIBONACCI);
+        assert_eq!(export_name(&parse_rules(FIBONACCI).unwrap()).unwrap(), FIBONACCI);
+
+        // Langton's ant, trmt's plain string rules export too
+        assert_eq!(export_name(&parse_rules("RL").unwrap()).unwrap(), "Turmite_120080");
+        assert_eq!(export_name(&parse_rules("Turmite_180020").unwrap()).unwrap(), "Turmite_180020");
+    }
+
+    #[test]
+    fn absolute_turmites_round_trip() {
+        let name = "AbsoluteTurmite_1N10S11S30N21W01N11S20E1";
+        let rules = parse_rules(name).unwrap();
+        assert_eq!(rules[&(0, 'A')].turn_direction, TurnDirection::Absolute(Direction::Up));
+        assert_eq!(rules[&(0, 'A')].new_cell_state, 'B');
+        assert_eq!(export_name(&rules).unwrap(), name);
+    }
+
+    #[test]
+    fn rule_tables_round_trip_through_their_name() {
+        let table = export_rule_table(&parse_rules("RL").unwrap()).unwrap();
+        assert!(table.starts_with("@RULE Turmite_120080\n"));
+        assert!(table.contains("n_states:10\n"));
+        // Facing north on blank it turns right, so the cell to its east takes it on heading east
+        assert!(table.contains("\n0,a,b,c,2,4\n"));
+        assert_eq!(import_rule_file(&table).unwrap(), "Turmite_120080");
+        assert!(import_rule_file("@RULE Life\n").is_err());
+    }
+
+    #[test]
+    fn unsupported_rules_are_refused() {
+        assert!(to_rule_string("Turmite_1a0").is_err());
+        assert!(to_rule_string("Turmite_18").is_err());
+        assert!(export_name(&parse_rules("R1>1,L0>0:N1>0,S0>1").unwrap()).is_err());
+        assert!(export_name(&parse_rules("NW").unwrap()).is_err());
+        assert!(export_name(&parse_rules("[R:0.9|L:0.1]L").unwrap()).is_err());
+    }
+}
@@ -1,11 +1,20 @@
 use ratatui::style::Color;
 use rustc_hash::FxHashMap;
+use std::collections::VecDeque;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Grid {
     pub tape: FxHashMap<(i32, i32), char>,
     pub tape_colors: FxHashMap<(i32, i32), Color>,
     pub tape_chars: FxHashMap<(i32, i32), String>,
+    // How often a head has stood on each cell, only counted while the heatmap is shown
+    pub visits: FxHashMap<(i32, i32), u32>,
+    counting_visits: bool,
+    max_visits: u32,
+    // Step each non-blank cell was last written at, kept while cells decay or fade by age
+    pub written_at: FxHashMap<(i32, i32), u64>,
+    // Writes in step order, only kept while cells decay
+    decay_queue: VecDeque<(u64, (i32, i32))>,
 }
 
 impl Grid {
@@ -16,6 +25,11 @@ impl Grid {
             tape: FxHashMap::with_capacity_and_hasher(8192, Default::default()),
             tape_colors: FxHashMap::with_capacity_and_hasher(8192, Default::default()),
             tape_chars: FxHashMap::with_capacity_and_hasher(8192, Default::default()),
+            visits: FxHashMap::default(),
+            counting_visits: false,
+            max_visits: 0,
+            written_at: FxHashMap::with_capacity_and_hasher(8192, Default::default()),
+            decay_queue: VecDeque::new(),
         }
     }
 
@@ -39,9 +53,91 @@ impl Grid {
         }
     }
 
+    #[inline]
+    pub fn visit(&mut self, x: i32, y: i32) {
+        if !self.counting_visits {
+            return;
+        }
+        let count = self.visits.entry((x, y)).or_default();
+        *count = count.saturating_add(1);
+        self.max_visits = self.max_visits.max(*count);
+    }
+
+    // Visits count from when this is turned on, and are dropped when it's turned off
+    pub fn count_visits(&mut self, enabled: bool) {
+        if !enabled && self.counting_visits {
+            self.visits = FxHashMap::default();
+            self.max_visits = 0;
+        }
+        self.counting_visits = enabled;
+    }
+
+    pub fn counts_visits(&self) -> bool {
+        self.counting_visits
+    }
+
+    pub fn max_visits(&self) -> u32 {
+        self.max_visits
+    }
+
+    // Blank writes leave nothing to age
+    pub fn mark_written(&mut self, x: i32, y: i32, state: char, step: u64, decaying: bool) {
+        if state == Self::EMPTY {
+            self.written_at.remove(&(x, y));
+            return;
+        }
+        self.written_at.insert((x, y), step);
+        if decaying {
+            self.decay_queue.push_back((step, (x, y)));
+        }
+    }
+
+    pub fn age(&self, x: i32, y: i32, now: u64) -> Option<u64> {
+        self.written_at.get(&(x, y)).map(|&step| now.saturating_sub(step))
+    }
+
+    // Next cell left unwritten for decay_after steps, rewritten cells are skipped
+    pub fn pop_expired(&mut self, now: u64, decay_after: u64) -> Option<(i32, i32)> {
+        while let Some(&(step, pos)) = self.decay_queue.front() {
+            if now.saturating_sub(step) < decay_after {
+                return None;
+            }
+            self.decay_queue.pop_front();
+            if self.written_at.get(&pos) == Some(&step) {
+                return Some(pos);
+            }
+        }
+        None
+    }
+
+    // Cells whose state differs between two tapes, blanks included
+    pub fn diff_cells(&self, other: &Grid) -> Vec<(i32, i32)> {
+        let mut cells: Vec<(i32, i32)> = self.tape.keys()
+            .chain(other.tape.keys().filter(|pos| !self.tape.contains_key(pos)))
+            .filter(|&&(x, y)| self.get_cell(x, y) != other.get_cell(x, y))
+            .copied()
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    // The cells and their ages, without the colours, glyphs and visits only drawing needs
+    pub fn without_display(&self) -> Self {
+        Self {
+            tape: self.tape.clone(),
+            written_at: self.written_at.clone(),
+            decay_queue: self.decay_queue.clone(),
+            ..Self::default()
+        }
+    }
+
     pub fn clear(&mut self) {
         self.tape.clear();
         self.tape_colors.clear();
         self.tape_chars.clear();
+        self.visits.clear();
+        self.max_visits = 0;
+        self.written_at.clear();
+        self.decay_queue.clear();
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use ratatui::style::Color;
 use std::collections::VecDeque;
 use super::rules::Direction;
+use super::watch::HeadWatch;
 
 #[derive(Debug, Clone)]
 pub struct Head {
@@ -11,6 +12,7 @@ pub struct Head {
     pub previous_direction: Option<Direction>,
     pub color: Color,
     pub trail: VecDeque<(i32, i32)>,
+    pub watch: HeadWatch,
 }
 
 impl Head {
@@ -23,11 +25,17 @@ impl Head {
             internal_state: 0,
             color,
             trail: VecDeque::with_capacity(20),
+            watch: HeadWatch::default(),
         }
     }
 
     #[inline]
     pub fn move_to(&mut self, x: i32, y: i32, trail_length: usize) {
+        // Turning in place leaves the trail as it is
+        if (x, y) == (self.x, self.y) {
+            return;
+        }
+        
         self.trail.push_back((self.x, self.y));
         
         if self.trail.len() > trail_length {
@@ -2,26 +2,70 @@ pub mod rules;
 pub mod grid;
 pub mod heads;
 pub mod detection;
+pub mod sensors;
+pub mod behaviour;
+pub mod watch;
+pub mod stats;
+pub mod tape;
+pub mod golly;
 
 use ratatui::style::Color;
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
-use crate::config::Config;
-use crate::machine::rules::Direction;
+use rand::seq::SliceRandom;
+use crate::config::{CharData, Config, HeadSchedule, Outcome, PaletteSpan, ReactionsConfig};
+use crate::machine::rules::{Direction, Movement};
 
 pub use rules::{StateTransition, TurnDirection};
 pub use heads::Head;
 pub use grid::Grid;
 pub use detection::{CycleDetector, DetectionStatus};
+pub use sensors::SensorTable;
+pub use behaviour::BehaviourTracker;
+pub use watch::HeadActivity;
+pub use stats::RunStats;
+pub use tape::InitialTape;
 
-#[derive(Debug)]
+// Head index, move, turn, next state, destination and color for one head
+type HeadUpdate = (usize, Movement, TurnDirection, usize, i32, i32, Color);
+
+// Cells drawn for a stuck head before it's left where it is
+const RESPAWN_TRIES: usize = 64;
+
+// The run as it stood when detection last started, replayed to find where a cycle begins
+#[derive(Debug, Clone)]
+struct Origin {
+    steps: u64,
+    grid: Grid,
+    heads: Vec<Head>,
+    rng: StdRng,
+}
+
+// Cycle onset being measured on its own thread, which stops once this is dropped
+#[derive(Debug, Default)]
+struct OnsetSearch {
+    onset: Arc<OnceLock<u64>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Drop for OnsetSearch {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct TuringMachine {
     pub grid: Grid,
     pub heads: Vec<Head>,
     pub rule_string: String,
     pub rules: BTreeMap<(usize, char), StateTransition>,
+    pub sensors: Option<SensorTable>,
     pub num_heads: usize,
     pub running: bool,
     pub steps: u64,
@@ -30,18 +74,48 @@ pub struct TuringMachine {
     pub grid_height: i32,
     colors: Vec<Color>,
     cached_parsed_colors: FxHashMap<String, Color>,
-    updates_buffer: Vec<(usize, char, TurnDirection, usize, i32, i32, Color)>,
+    updates_buffer: Vec<HeadUpdate>,
+    schedule_order: Vec<usize>,
+    writes_buffer: Vec<(i32, i32, char, Color, Option<String>)>,
     pub dirty_cells: FxHashSet<(i32, i32)>,
     head_char_sequence: Vec<usize>,
     trail_char_sequence: Vec<usize>,
     sequence_length: usize,
     pub detector: CycleDetector,
+    pub behaviour: BehaviourTracker,
+    pub stats: RunStats,
+    origin: Origin,
+    onset_search: Option<Arc<OnsetSearch>>,
     pub has_looped: bool,
+    highway_reaction: bool,
     pub auto_halted: bool,
+    // Draws for stochastic transitions and random head order, reseeded with every run
+    rng: StdRng,
+    stochastic: bool,
+    // Random head order and respawns make the RNG part of the state, set from config with every spawn
+    random_schedule: bool,
+    respawning: bool,
+    // Why the rule didn't parse, heads stop where a transition is missing
+    pub rule_error: Option<String>,
+    // States the current rule uses, gradients are spread over them
+    cell_states: usize,
+    // Steps a cell stays written before it reverts to blank, 0 keeps it forever
+    decay_after: u64,
+    // Seed and rule that override config and state, for comparison universes
+    pinned: Option<(String, String)>,
 }
 
 impl TuringMachine {
     pub fn new(num_heads: usize, rule_string: &str, config: &Config) -> Self {
+        Self::build(num_heads, rule_string, None, config)
+    }
+
+    // A machine with its own seed and rule that never touches the state files
+    pub fn pinned(num_heads: usize, seed: &str, rule_string: &str, config: &Config) -> Self {
+        Self::build(num_heads, rule_string, Some((seed.to_string(), rule_string.to_string())), config)
+    }
+
+    fn build(num_heads: usize, rule_string: &str, pinned: Option<(String, String)>, config: &Config) -> Self {
         let sequence_length = 10000;
 
         let mut machine = Self {
@@ -49,6 +123,7 @@ impl TuringMachine {
             heads: Vec::with_capacity(num_heads.min(256)),
             rule_string: rule_string.to_string(),
             rules: BTreeMap::new(),
+            sensors: None,
             num_heads: num_heads.min(256),
             running: config.simulation.autoplay,
             steps: 0,
@@ -58,17 +133,31 @@ impl TuringMachine {
             colors: Vec::new(),
             cached_parsed_colors: FxHashMap::default(),
             updates_buffer: Vec::with_capacity(256),
+            schedule_order: Vec::with_capacity(256),
+            writes_buffer: Vec::with_capacity(256),
             dirty_cells: FxHashSet::with_capacity_and_hasher(1024, Default::default()),
             head_char_sequence: Vec::with_capacity(sequence_length),
             trail_char_sequence: Vec::with_capacity(sequence_length),
             sequence_length,
             detector: CycleDetector::new(),
+            behaviour: BehaviourTracker::new(),
+            stats: RunStats::new(),
+            origin: Origin { steps: 0, grid: Grid::new(), heads: Vec::new(), rng: StdRng::seed_from_u64(0) },
+            onset_search: None,
             has_looped: false,
+            highway_reaction: false,
             auto_halted: false,
+            rng: StdRng::seed_from_u64(0),
+            stochastic: false,
+            random_schedule: false,
+            respawning: false,
+            rule_error: None,
+            decay_after: 0,
+            cell_states: 1,
+            pinned,
         };
 
         machine.update_colors(config);
-        machine.parse_rules(rule_string);
         machine.spawn_heads(config);
         machine
     }
@@ -101,7 +190,9 @@ impl TuringMachine {
         self.heads.clear();
         self.heads.reserve(self.num_heads);
         
-        let seed = if let Some(effective_seed) = config.get_effective_seed() {
+        let seed = if let Some((pinned_seed, _)) = &self.pinned {
+            pinned_seed.clone()
+        } else if let Some(effective_seed) = config.get_effective_seed() {
             if !effective_seed.is_empty() {
                 effective_seed
             } else {
@@ -112,9 +203,14 @@ impl TuringMachine {
         };
         
         self.current_seed = seed.clone();
+        self.random_schedule = config.simulation.schedule == HeadSchedule::Random;
+        self.respawning = config.simulation.respawn_stuck_heads;
         
-        let effective_rule = config.get_effective_rule();
-        self.parse_rules(&effective_rule);
+        let effective_rule = match &self.pinned {
+            Some((_, pinned_rule)) => pinned_rule.clone(),
+            None => config.get_effective_rule(),
+        };
+        self.rule_error = self.parse_rules(&effective_rule).err();
         self.rule_string = effective_rule;
         
         // Get initial direction from rule
@@ -122,22 +218,45 @@ impl TuringMachine {
         
         let seed_hash = self.hash_seed(&seed);
         let mut rng = StdRng::seed_from_u64(seed_hash);
+        self.rng = StdRng::seed_from_u64(seed_hash.wrapping_add(67890));
 
         for i in 0..self.num_heads {
             let x = rng.random_range(0..self.grid_width.max(1));
             let y = rng.random_range(0..self.grid_height.max(1));
             let mut head = Head::new(x, y, Color::White);
             head.direction = initial_direction;
-            head.color = config.display.get_head_color(i);
+            head.color = config.display.get_head_color(i, self.palette_span());
             self.heads.push(head);
         }
         
+        self.update_detection();
+        self.detector.set_phase_period(config.simulation.schedule_period());
+        self.decay_after = config.simulation.decay_after;
+        self.detector.set_decay(self.decay_after > 0);
+        self.detector.set_bounds(self.grid_width, self.grid_height);
+        self.detector.set_memory_budget(config.simulation.detection_memory_mb.saturating_mul(1 << 20));
+        self.highway_reaction = config.reactions.reacts_to_highways(&config.simulation);
+        
         self.generate_random_sequences(config);
         self.reset_detection();
     }
 
+    // Cancels any onset search, which measured the run before this point
+    fn mark_origin(&mut self) {
+        self.origin = Origin {
+            steps: self.steps,
+            grid: self.grid.without_display(),
+            heads: self.heads.clone(),
+            rng: self.rng.clone(),
+        };
+        self.onset_search = None;
+    }
+
     fn reset_detection(&mut self) {
-        self.detector.reset_with(&self.grid, &self.heads);
+        self.detector.reset_with(&self.grid, &self.heads, self.steps);
+        self.behaviour.reset(&self.grid);
+        self.stats.reset(&self.grid, self.heads.len(), self.steps);
+        self.mark_origin();
         self.has_looped = false;
         self.auto_halted = false;
     }
@@ -145,21 +264,19 @@ impl TuringMachine {
     // Calculate char based on direction
     fn get_head_char(&self, head: &Head, new_direction: Direction, config: &Config) -> Option<String> {
         if config.display.direction_based_chars {
-            let char_index = config.display.get_direction_char_index(new_direction, Some(head.direction));
-            let index = char_index % config.display.head_char.len();
-            Some(config.display.head_char[index].clone())
+            Some(config.display.direction_glyph(Some(head.direction), new_direction).to_string())
         } else {
             None
         }
     }
 
-    pub fn get_head_char_index(&self, head_index: usize, config: &Config) -> usize {
+    pub fn get_head_char_data<'a>(&self, head_index: usize, config: &'a Config) -> &'a CharData {
         if config.display.randomize_heads {
             let sequence_index = (self.steps.wrapping_add(head_index as u64)) as usize % self.sequence_length;
-            self.head_char_sequence[sequence_index] % config.display.head_char_data.len()
+            &config.display.head_char_data[self.head_char_sequence[sequence_index] % config.display.head_char_data.len()]
         } else {
             let head = &self.heads[head_index];
-            config.display.get_head_char_index(
+            config.display.get_head_char_data(
                 head_index, 
                 head.direction, 
                 head.previous_direction
@@ -185,8 +302,9 @@ impl TuringMachine {
             .map(|c| self.parse_color_cached(c, config))
             .collect();
         
+        let span = self.palette_span();
         for (i, head) in self.heads.iter_mut().enumerate() {
-            head.color = config.display.get_head_color(i);
+            head.color = config.display.get_head_color(i, span);
         }
     }
 
@@ -219,8 +337,36 @@ impl TuringMachine {
         hasher.finish()
     }
 
-    pub fn parse_rules(&mut self, rule_string: &str) {
-        self.rules = rules::parse_rules(rule_string);
+    // An invalid rule leaves no transitions, so the run stalls rather than playing something else
+    pub fn parse_rules(&mut self, rule_string: &str) -> Result<(), String> {
+        let (base, sensor_section) = sensors::split_sensor_section(rule_string);
+        let parsed = rules::parse_rules(base).and_then(|rules| {
+            let table = sensor_section.map(sensors::parse_sensor_table).transpose()
+                .map_err(|e| format!("sensor table: {}", e))?;
+            Ok((rules, table))
+        });
+        let result = parsed.as_ref().map(|_| ()).map_err(String::clone);
+        (self.rules, self.sensors) = parsed.unwrap_or_default();
+        self.cell_states = rules::cell_state_count(&self.rules);
+        self.stochastic = rules::is_stochastic(&self.rules)
+            || self.sensors.as_ref().is_some_and(|t| t.entries.values().any(|e| e.is_stochastic()));
+        self.update_detection();
+        result
+    }
+
+    // Repeats can't be proven once the RNG is part of the state, highways only need each head to be deterministic
+    fn update_detection(&mut self) {
+        let shuffled = self.random_schedule && self.num_heads > 1;
+        self.detector.set_cycle_detection(!self.stochastic && !shuffled && !self.respawning);
+        self.detector.set_highway_detection(!self.stochastic);
+    }
+
+    pub fn palette_span(&self) -> PaletteSpan {
+        PaletteSpan { states: self.cell_states, heads: self.num_heads }
+    }
+
+    pub fn is_stochastic(&self) -> bool {
+        self.stochastic
     }
 
     #[inline(always)]
@@ -241,73 +387,296 @@ impl TuringMachine {
         self.dirty_cells.clear();
     }
 
+    // Sensor entries take precedence over the base rule
+    fn lookup<'a>(
+        rules: &'a BTreeMap<(usize, char), StateTransition>,
+        sensors: Option<&'a SensorTable>,
+        grid: &Grid,
+        head: &Head,
+        current_cell: char,
+        width: i32,
+        height: i32,
+    ) -> Option<&'a StateTransition> {
+        let sensed = sensors.and_then(|table| {
+            let reading = table.sensor.read(grid, head.x, head.y, head.direction, width, height);
+            table.get(head.internal_state, current_cell, reading)
+        });
+        sensed.or_else(|| rules.get(&(head.internal_state, current_cell)))
+    }
+
+    fn all_heads_stuck(&self, width: i32, height: i32) -> bool {
+        !self.heads.is_empty() && self.heads.iter().all(|head| {
+            let current_cell = self.get_cell(head.x, head.y);
+            Self::lookup(&self.rules, self.sensors.as_ref(), &self.grid, head, current_cell, width, height).is_none()
+        })
+    }
+
     pub fn step(&mut self, width: i32, height: i32, config: &Config) {
         self.updates_buffer.clear();
         self.updates_buffer.reserve(self.heads.len());
+        self.detector.set_bounds(width, height);
 
-        for (i, head) in self.heads.iter().enumerate() {
-            let current_cell = self.get_cell(head.x, head.y);
-            
-            if let Some(transition) = self.rules.get(&(head.internal_state, current_cell)) {
-                let new_direction = transition.turn_direction.apply(head.direction);
-                let (new_x, new_y) = new_direction.apply(head.x, head.y);
-                let wrapped_x = ((new_x % width) + width) % width;
-                let wrapped_y = ((new_y % height) + height) % height;
-                
-                let live_colors_color = if config.display.state_based_colors && config.display.live_colors {
-                    config.display.get_cell_color(transition.new_cell_state, i)
-                } else {
-                    config.display.get_head_color(i)
-                };
-                
-                self.updates_buffer.push((
-                    i,
-                    transition.new_cell_state,
-                    transition.turn_direction,
-                    transition.new_internal_state,
-                    wrapped_x,
-                    wrapped_y,
-                    live_colors_color,
-                ));
-                
-                let display_char = if config.simulation.color_cells ||
-                (config.display.direction_based_chars && config.simulation.trail_length > 0) {
-                    self.get_head_char(head, new_direction, config)
-                } else {
-                    None
-                };
-        
-                let cell_color = config.display.get_cell_color(transition.new_cell_state, i);
-                self.grid.set_cell(
-                    head.x,
-                    head.y,
-                    transition.new_cell_state,
-                    cell_color,
-                    display_char,
-                    config.display.state_based_colors
-                );
-                self.detector.cell_delta(head.x, head.y, current_cell, transition.new_cell_state);
-                self.dirty_cells.insert((head.x, head.y));
+        // Slower heads sit out the steps between their turns
+        self.schedule_order.clear();
+        self.schedule_order.extend(
+            (0..self.heads.len()).filter(|&i| self.steps.is_multiple_of(config.simulation.head_interval(i)))
+        );
+        if config.simulation.schedule == HeadSchedule::Random {
+            self.schedule_order.shuffle(&mut self.rng);
+        }
+        let lockstep = config.simulation.schedule == HeadSchedule::Lockstep;
+
+        for order_index in 0..self.schedule_order.len() {
+            let i = self.schedule_order[order_index];
+            // Sequential heads write and move before the next one reads
+            if self.plan_head(i, width, height, config) && !lockstep {
+                self.flush_writes(config);
+                let update = self.updates_buffer[self.updates_buffer.len() - 1];
+                self.apply_update(update, width, height, config.simulation.trail_length);
             }
         }
 
-        for &(i, _, turn_direction, new_internal_state, x, y, live_color) in &self.updates_buffer {
-            let head = &mut self.heads[i];
-            let old = (head.x, head.y, head.direction, head.internal_state);
-            let new_direction = turn_direction.apply(head.direction);
-            head.set_direction(new_direction);
-            head.internal_state = new_internal_state;
-            head.color = live_color;
-            head.move_to(x, y, config.simulation.trail_length);
-            self.detector.head_delta(i, old, (x, y, new_direction, new_internal_state));
+        // Lockstep heads all read the tape as it was before anyone wrote
+        if lockstep {
+            self.flush_writes(config);
+            for update_index in 0..self.updates_buffer.len() {
+                let update = self.updates_buffer[update_index];
+                self.apply_update(update, width, height, config.simulation.trail_length);
+            }
+        }
+
+        if config.simulation.respawn_stuck_heads {
+            self.respawn_stuck_heads(width, height);
         }
 
         self.steps += 1;
-        if self.updates_buffer.is_empty() && !self.heads.is_empty() {
+        if self.decay_after > 0 {
+            self.decay_cells(config);
+        }
+        self.behaviour.on_step_end(self.steps);
+        self.stats.on_step_end(self.steps);
+        // Cells still to decay can give stuck heads a rule again
+        let decay_pending = self.decay_after > 0 && !self.grid.written_at.is_empty();
+        if self.updates_buffer.is_empty() && !decay_pending && self.all_heads_stuck(width, height) {
             self.detector.mark_stalled(self.steps);
         } else {
             self.detector.on_step_end(&self.grid, &self.heads, self.steps);
+            if let DetectionStatus::Cycle { at_step, period, .. } = self.detector.status()
+                && at_step == self.steps
+            {
+                self.onset_search = Some(Arc::new(self.search_onset(period, width, height, config)));
+            }
+        }
+    }
+
+    // Replays from the origin with a second copy one period ahead, off the caller's thread.
+    // The first step where both agree is where the cycle starts
+    fn search_onset(&self, period: u64, width: i32, height: i32, config: &Config) -> OnsetSearch {
+        let search = OnsetSearch::default();
+        let (onset, cancelled) = (search.onset.clone(), search.cancelled.clone());
+        let mut tortoise = self.replay_from_origin();
+        let config = config.clone();
+        let until = self.steps;
+        thread::spawn(move || {
+            let mut hare = tortoise.clone();
+            for _ in 0..period {
+                if cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                hare.step(width, height, &config);
+            }
+            while tortoise.steps < until {
+                if cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                if tortoise.detector.fingerprint() == hare.detector.fingerprint()
+                    && detection::same_state(&tortoise.grid, &tortoise.heads, &hare.grid, &hare.heads, tortoise.age_clocks(&hare))
+                {
+                    break;
+                }
+                tortoise.step(width, height, &config);
+                hare.step(width, height, &config);
+            }
+            let _ = onset.set(tortoise.steps);
+        });
+        search
+    }
+
+    // Fills in the cycle onset once its search has finished
+    pub fn poll_onset(&mut self) {
+        if let Some(&onset) = self.onset_search.as_ref().and_then(|search| search.onset.get()) {
+            self.detector.record_onset(onset);
+            self.onset_search = None;
+        }
+    }
+
+    // Blocks until the cycle onset is known, for callers that report it once
+    pub fn wait_for_onset(&mut self) {
+        if let Some(search) = self.onset_search.take() {
+            self.detector.record_onset(*search.onset.wait());
+        }
+    }
+
+    // Copy of this run rebuilt from its origin, with no history or detection of its own.
+    // Everything else is cloned, so the replay steps exactly as the run did
+    fn replay_from_origin(&self) -> TuringMachine {
+        let mut replay = self.clone();
+        replay.grid = self.origin.grid.clone();
+        replay.heads = self.origin.heads.clone();
+        replay.steps = self.origin.steps;
+        replay.rng = self.origin.rng.clone();
+        replay.detector = CycleDetector::new();
+        replay.behaviour = BehaviourTracker::new();
+        replay.stats = RunStats::new();
+        replay.onset_search = None;
+        replay.dirty_cells.clear();
+        // Only the running fingerprint is needed, so checkpoints keep no tape
+        replay.detector.set_cycle_detection(false);
+        replay.detector.set_highway_detection(false);
+        replay.detector.set_decay(self.decay_after > 0);
+        replay.detector.set_memory_budget(0);
+        replay.detector.reset_with(&replay.grid, &replay.heads, replay.steps);
+        replay
+    }
+
+    // Reads the tape for one head and queues its write and move, false if it has no rule
+    fn plan_head(&mut self, i: usize, width: i32, height: i32, config: &Config) -> bool {
+        let head = &self.heads[i];
+        let current_cell = self.get_cell(head.x, head.y);
+        let Some(transition) = Self::lookup(&self.rules, self.sensors.as_ref(), &self.grid, head, current_cell, width, height) else {
+            self.heads[i].watch.on_no_rule();
+            return false;
+        };
+        let modified = transition.writes && transition.new_cell_state != current_cell;
+        
+        let turn_direction = transition.pick_turn(&mut self.rng);
+        self.stats.visit(head.x, head.y, turn_direction);
+        self.grid.visit(head.x, head.y);
+        let new_direction = turn_direction.apply(head.direction);
+        let (new_x, new_y) = new_direction.advance(head.x, head.y, transition.movement);
+        let wrapped_x = ((new_x % width) + width) % width;
+        let wrapped_y = ((new_y % height) + height) % height;
+        
+        let live_colors_color = if config.display.state_based_colors && config.display.live_colors {
+            config.display.get_cell_color(transition.new_cell_state, i, self.palette_span())
+        } else {
+            config.display.get_head_color(i, self.palette_span())
+        };
+        
+        self.updates_buffer.push((
+            i,
+            transition.movement,
+            turn_direction,
+            transition.new_internal_state,
+            wrapped_x,
+            wrapped_y,
+            live_colors_color,
+        ));
+        
+        if transition.writes {
+            let display_char = if config.simulation.color_cells ||
+            (config.display.direction_based_chars && config.simulation.trail_length > 0) {
+                self.get_head_char(head, new_direction, config)
+            } else {
+                None
+            };
+            
+            let cell_color = config.display.get_cell_color(transition.new_cell_state, i, self.palette_span());
+            self.writes_buffer.push((head.x, head.y, transition.new_cell_state, cell_color, display_char));
         }
+        self.dirty_cells.insert((head.x, head.y));
+        self.heads[i].watch.on_act(wrapped_x, wrapped_y, modified, (width, height));
+        true
+    }
+
+    // Moves heads that stalled, orbit or idle to a random cell off their own and other heads'.
+    // A head stays put when a few draws find nothing free
+    fn respawn_stuck_heads(&mut self, width: i32, height: i32) {
+        for i in 0..self.heads.len() {
+            if self.heads[i].watch.activity() == HeadActivity::Active {
+                continue;
+            }
+            let free = |heads: &[Head], (x, y): (i32, i32)| heads.iter().all(|head| (head.x, head.y) != (x, y));
+            let Some((x, y)) = (0..RESPAWN_TRIES)
+                .map(|_| (self.rng.random_range(0..width.max(1)), self.rng.random_range(0..height.max(1))))
+                .find(|&cell| free(&self.heads, cell))
+            else {
+                continue;
+            };
+            let head = &mut self.heads[i];
+            let old = (head.x, head.y, head.direction, head.internal_state);
+            head.x = x;
+            head.y = y;
+            head.trail.clear();
+            head.watch = Default::default();
+            self.detector.head_delta(i, old, (x, y, old.2, old.3));
+            self.dirty_cells.insert((old.0, old.1));
+        }
+    }
+
+    // Cell ages are only kept while decay or cell_age_fade reads them
+    fn ages_cells(&self, config: &Config) -> bool {
+        self.decay_after > 0 || config.display.cell_age_fade > 0
+    }
+
+    fn flush_writes(&mut self, config: &Config) {
+        let aging = self.ages_cells(config);
+        for (x, y, state, color, display_char) in self.writes_buffer.drain(..) {
+            // Heads sharing a cell overwrite each other, so hash against what's there now
+            let old = self.grid.get_cell(x, y);
+            let old_written = self.grid.written_at.get(&(x, y)).copied();
+            self.grid.set_cell(x, y, state, color, display_char, config.display.state_based_colors);
+            if aging {
+                self.grid.mark_written(x, y, state, self.steps, self.decay_after > 0);
+            }
+            let new_written = self.grid.written_at.get(&(x, y)).copied();
+            self.detector.cell_delta(x, y, old, state);
+            self.detector.write_delta(x, y, (old, old_written), (state, new_written));
+            self.behaviour.cell_delta(old, state);
+            self.stats.cell_delta(old, state);
+        }
+    }
+
+    // Reverts cells nobody has written for decay_after steps
+    fn decay_cells(&mut self, config: &Config) {
+        let blank_color = config.display.get_cell_color(Grid::EMPTY, 0, self.palette_span());
+        while let Some((x, y)) = self.grid.pop_expired(self.steps, self.decay_after) {
+            let old = self.grid.get_cell(x, y);
+            let old_written = self.grid.written_at.remove(&(x, y));
+            self.grid.set_cell(x, y, Grid::EMPTY, blank_color, None, config.display.state_based_colors);
+            self.grid.tape_chars.remove(&(x, y));
+            self.detector.cell_delta(x, y, old, Grid::EMPTY);
+            self.detector.write_delta(x, y, (old, old_written), (Grid::EMPTY, None));
+            self.behaviour.cell_delta(old, Grid::EMPTY);
+            self.stats.cell_delta(old, Grid::EMPTY);
+        }
+    }
+
+    // Step counts of two copies of this run, when cell ages are part of its state
+    fn age_clocks(&self, other: &TuringMachine) -> Option<(u64, u64)> {
+        (self.decay_after > 0).then_some((self.steps, other.steps))
+    }
+
+    fn apply_update(&mut self, update: HeadUpdate, width: i32, height: i32, trail_length: usize) {
+        let (i, movement, turn_direction, new_internal_state, x, y, live_color) = update;
+        let head = &mut self.heads[i];
+        let old = (head.x, head.y, head.direction, head.internal_state);
+        let new_direction = turn_direction.apply(head.direction);
+        head.set_direction(new_direction);
+        head.internal_state = new_internal_state;
+        head.color = live_color;
+        // Long straight moves leave a continuous trail, jumps leave a gap
+        if !movement.is_jump() {
+            for distance in 1..movement.forward {
+                let (px, py) = new_direction.advance(old.0, old.1, Movement { forward: distance, side: 0 });
+                let wrapped = (((px % width) + width) % width, ((py % height) + height) % height);
+                head.move_to(wrapped.0, wrapped.1, trail_length);
+            }
+        }
+        head.move_to(x, y, trail_length);
+        self.detector.head_delta(i, old, (x, y, new_direction, new_internal_state));
+        let (dx, dy) = new_direction.advance(0, 0, movement);
+        self.stats.head_moved(i, dx, dy);
     }
 
     pub fn tape_chars(&self) -> &FxHashMap<(i32, i32), String> {
@@ -318,7 +687,27 @@ impl TuringMachine {
         self.running = !self.running;
     }
 
+    // Runs this seed and rule, or a random seed without one, and keeps them out of the
+    // state files. Restarts replay it until it's released
+    pub fn pin(&mut self, seed: Option<&str>, rule: &str) {
+        let seed = match seed {
+            Some(seed) if !seed.is_empty() => seed.to_string(),
+            _ => self.generate_random_seed(),
+        };
+        self.pinned = Some((seed, rule.to_string()));
+    }
+
+    // Hands a pinned run over to the state files, so the next spawn carries on from it
+    pub fn release(&mut self) {
+        if self.pinned.take().is_some() {
+            self.save_state();
+        }
+    }
+
     fn save_state(&self) {
+        if self.pinned.is_some() {
+            return;
+        }
         let _ = Config::save_current_seed(&self.current_seed);
         let _ = Config::save_current_rule(&self.rule_string);
     }
@@ -335,6 +724,27 @@ impl TuringMachine {
         self.grid.clear();
         self.dirty_cells.clear();
         self.spawn_heads(config);
+        self.lay_initial_tape(config);
+    }
+
+    // Writes the tape cached from simulation.initial_tape onto a cleared grid, random fills follow the seed
+    fn lay_initial_tape(&mut self, config: &Config) {
+        let tape = &config.simulation.tape;
+        let mut rng = StdRng::seed_from_u64(self.hash_seed(&self.current_seed).wrapping_add(24680));
+        let span = self.palette_span();
+        let aging = self.ages_cells(config);
+        let colors: Vec<Color> = (0..span.states).map(|i| config.display.palette_data.color(i, span.states)).collect();
+        for (x, y, state) in tape.cells(self.grid_width, self.grid_height, self.cell_states, &colors, &mut rng) {
+            let color = config.display.get_cell_color(state, 0, span);
+            self.grid.set_cell(x, y, state, color, None, config.display.state_based_colors);
+            if aging {
+                self.grid.mark_written(x, y, state, self.steps, self.decay_after > 0);
+            }
+            self.dirty_cells.insert((x, y));
+        }
+        if *tape != InitialTape::Blank {
+            self.reset_detection();
+        }
     }
 
     // Replay the current run, saving state on the first restart only
@@ -352,8 +762,69 @@ impl TuringMachine {
         self.auto_halted = true;
     }
 
-    pub fn detection_pending(&self) -> bool {
-        self.detector.status() != DetectionStatus::Running && !self.auto_halted
+    // What the run has come to, if it still waits on a reaction. Highways only
+    // count when the config reacts to them
+    pub fn pending_outcome(&self, reactions: &ReactionsConfig) -> Option<Outcome> {
+        if self.auto_halted {
+            return None;
+        }
+        match self.detector.status() {
+            DetectionStatus::Stalled { .. } => Some(Outcome::Stall),
+            DetectionStatus::Cycle { .. } => Some(Outcome::Cycle),
+            DetectionStatus::Highway { .. } if self.highway_reaction => Some(Outcome::Highway),
+            _ if reactions.timed_out(self.steps) => Some(Outcome::Timeout),
+            _ => None,
+        }
+    }
+
+    // Plain text dump of the visible tape, '.' for blank cells
+    pub fn snapshot_text(&self) -> String {
+        let mut text = format!(
+            "# rule: {}\n# seed: {}\n# steps: {}\n# status: {}\n",
+            self.rule_string, self.current_seed, self.steps, self.detector.status()
+        );
+        for y in 0..self.grid_height {
+            text.extend((0..self.grid_width).map(|x| match self.grid.get_cell(x, y) {
+                Grid::EMPTY => '.',
+                state => state,
+            }));
+            text.push('\n');
+        }
+        text
+    }
+
+    // Visit counts on the heatmap's log scale, 0 to 9, '.' for cells no head stood on
+    pub fn heatmap_text(&self) -> String {
+        let max = (self.grid.max_visits() as f32).ln_1p();
+        let mut text = String::new();
+        for y in 0..self.grid_height {
+            text.extend((0..self.grid_width).map(|x| match self.grid.visits.get(&(x, y)) {
+                Some(&visits) if max > 0.0 => char::from(b'0' + ((visits as f32).ln_1p() / max * 9.0).round() as u8),
+                _ => '.',
+            }));
+            text.push('\n');
+        }
+        text
+    }
+
+    // The tape drawn with each state's glyph, blank cells as spaces
+    pub fn glyph_text(&self, config: &Config) -> String {
+        let mut text = String::new();
+        for y in 0..self.grid_height {
+            for x in 0..self.grid_width {
+                let glyph = match self.grid.get_cell(x, y) {
+                    Grid::EMPTY if !config.display.state_based_colors => "  ".to_string(),
+                    state => config.display.get_state_char_data(state).chars.iter().collect(),
+                };
+                // Pad to the two columns a cell takes on screen
+                let width = crate::config::display::display_width(&glyph);
+                text.push_str(&glyph);
+                text.extend(std::iter::repeat_n(' ', 2usize.saturating_sub(width)));
+            }
+            text.truncate(text.trim_end_matches(' ').len());
+            text.push('\n');
+        }
+        text
     }
 
     pub fn set_head_count(&mut self, count: usize, config: &Config) {
@@ -361,15 +832,58 @@ impl TuringMachine {
         self.spawn_heads(config);
     }
 
-    pub fn update_grid_dimensions(&mut self, width: i32, height: i32) {
+    // Writes a cell by hand, outside the rules
+    pub fn paint_cell(&mut self, x: i32, y: i32, state: char, config: &Config) {
+        if x < 0 || y < 0 || x >= self.grid_width || y >= self.grid_height || self.grid.get_cell(x, y) == state {
+            return;
+        }
+        let old = self.grid.get_cell(x, y);
+        let color = config.display.get_cell_color(state, 0, self.palette_span());
+        self.grid.set_cell(x, y, state, color, None, config.display.state_based_colors);
+        self.grid.tape_chars.remove(&(x, y));
+        if self.ages_cells(config) {
+            self.grid.mark_written(x, y, state, self.steps, self.decay_after > 0);
+        }
+        self.behaviour.cell_delta(old, state);
+        self.stats.cell_delta(old, state);
+        self.dirty_cells.insert((x, y));
+        self.rebase_detection();
+    }
+
+    // Adds a head by hand, it takes the next head colour and starts in state 0
+    pub fn place_head(&mut self, x: i32, y: i32, direction: Direction, config: &Config) {
+        if x < 0 || y < 0 || x >= self.grid_width || y >= self.grid_height || self.heads.len() >= 256 {
+            return;
+        }
+        let mut head = Head::new(x, y, Color::White);
+        head.direction = direction;
+        head.color = config.display.get_head_color(self.heads.len(), self.palette_span());
+        self.heads.push(head);
+        self.num_heads = self.heads.len();
+        self.stats.heads.push(stats::HeadStats::default());
+        self.rebase_detection();
+    }
+
+    // A cycle can't span a hand edit, so detection starts over from the edited state
+    fn rebase_detection(&mut self) {
+        self.update_detection();
+        self.detector.reset_with(&self.grid, &self.heads, self.steps);
+        self.mark_origin();
+        self.has_looped = false;
+        self.auto_halted = false;
+    }
+
+    pub fn update_grid_dimensions(&mut self, width: i32, height: i32, config: &Config) {
         if self.grid_width != width || self.grid_height != height {
-            // Clear existing cells when dimensions change
+            // Clear existing cells when dimensions change, the initial tape is laid out for the new size
+            self.grid_width = width;
+            self.grid_height = height;
             self.grid.clear();
             self.dirty_cells.clear();
+            self.detector.set_bounds(width, height);
             self.reset_detection();
+            self.lay_initial_tape(config);
         }
-        self.grid_width = width;
-        self.grid_height = height;
     }
 
     pub fn tape(&self) -> &FxHashMap<(i32, i32), char> {
@@ -385,12 +899,26 @@ impl TuringMachine {
 mod tests {
     use super::*;
     use crate::machine::rules::TurnDirection;
+    use crate::machine::behaviour::Behaviour;
+
+    #[test]
+    fn invalid_sensor_tables_are_reported_not_dropped() {
+        let mut machine = TuringMachine::new(1, "RL", &Config::default());
+        let error = machine.parse_rules("RL@sideways;0.0.0=L").unwrap_err();
+        assert!(error.contains("unknown sensor"));
+        assert!(machine.rules.is_empty() && machine.sensors.is_none());
+
+        assert_eq!(machine.parse_rules("RL@moore;0.0.0=N"), Ok(()));
+        assert!(machine.sensors.is_some());
+    }
 
     // 1-head machine with hand-authored rules, no seed or state files
     fn test_machine(transitions: &[((usize, char), StateTransition)]) -> (TuringMachine, Config) {
         let config = Config::default();
         let mut m = TuringMachine::new(1, "RL", &config);
         m.rules = transitions.iter().cloned().collect();
+        m.detector.set_cycle_detection(!rules::is_stochastic(&m.rules));
+        m.detector.set_highway_detection(!rules::is_stochastic(&m.rules));
         m.heads.truncate(1);
         m.heads[0].x = 4;
         m.heads[0].y = 4;
@@ -398,7 +926,7 @@ mod tests {
         m.heads[0].internal_state = 0;
         m.grid.clear();
         m.steps = 0;
-        m.detector.reset_with(&m.grid, &m.heads);
+        m.reset_detection();
         (m, config)
     }
 
@@ -407,7 +935,7 @@ mod tests {
         // Paints a 2x2 box then hits its own 'B' with no rule and freezes
         let (mut m, config) = test_machine(&[(
             (0, 'A'),
-            StateTransition { new_cell_state: 'B', turn_direction: TurnDirection::Right, new_internal_state: 0 },
+            StateTransition::new('B', TurnDirection::Right, 0),
         )]);
         for _ in 0..100 {
             m.step(8, 8, &config);
@@ -415,12 +943,103 @@ mod tests {
         assert!(matches!(m.detector.status(), DetectionStatus::Stalled { .. }));
     }
 
+    #[test]
+    fn decay_frees_a_walled_in_head_into_a_cycle() {
+        // Same box as above, but the 'B' it froze on wears off and it goes round again
+        let (mut m, mut config) = test_machine(&[(
+            (0, 'A'),
+            StateTransition::new('B', TurnDirection::Right, 0),
+        )]);
+        config.simulation.decay_after = 10;
+        m.decay_after = 10;
+        m.detector.set_decay(true);
+        m.reset_detection();
+        for _ in 0..500 {
+            m.step(8, 8, &config);
+            if m.detector.status() != DetectionStatus::Running {
+                break;
+            }
+        }
+        let DetectionStatus::Cycle { period, .. } = m.detector.status() else {
+            panic!("expected cycle, got {:?}", m.detector.status());
+        };
+        assert!(m.grid.written_at.values().all(|&step| m.steps - step < 10));
+
+        // Incremental age hash agrees with a fresh one
+        let mut fresh = CycleDetector::new();
+        fresh.set_decay(true);
+        fresh.reset_with(&m.grid, &m.heads, m.steps);
+        assert_eq!(fresh.fingerprint(), m.detector.fingerprint());
+
+        // One period on, tape, heads and cell ages all repeat
+        let mut later = m.clone();
+        for _ in 0..period {
+            later.step(8, 8, &config);
+        }
+        assert!(detection::same_state(&m.grid, &m.heads, &later.grid, &later.heads, m.age_clocks(&later)));
+        assert!(!detection::same_state(&m.grid, &m.heads, &later.grid, &later.heads, Some((m.steps, later.steps + 1))));
+    }
+
+    #[test]
+    fn hand_edits_restart_detection_from_the_edited_state() {
+        let (mut m, config) = test_machine(&[(
+            (0, 'A'),
+            StateTransition::new('A', TurnDirection::Right, 0),
+        )]);
+        m.grid_width = 8;
+        m.grid_height = 8;
+        let run = |m: &mut TuringMachine| {
+            for _ in 0..100 {
+                m.step(8, 8, &config);
+                if m.detector.status() != DetectionStatus::Running {
+                    break;
+                }
+            }
+            m.detector.status()
+        };
+        assert!(matches!(run(&mut m), DetectionStatus::Cycle { period: 4, .. }));
+
+        // Off the orbit, so the head keeps circling once detection catches up again
+        m.paint_cell(0, 0, 'B', &config);
+        assert_eq!(m.detector.status(), DetectionStatus::Running);
+        assert_eq!(m.stats.histogram().collect::<Vec<_>>(), vec![('B', 1)]);
+        let mut fresh = CycleDetector::new();
+        fresh.reset_with(&m.grid, &m.heads, m.steps);
+        assert_eq!(fresh.fingerprint(), m.detector.fingerprint());
+        assert!(matches!(run(&mut m), DetectionStatus::Cycle { period: 4, .. }));
+
+        m.place_head(6, 6, Direction::Left, &config);
+        assert_eq!((m.heads.len(), m.num_heads, m.stats.heads.len()), (2, 2, 2));
+        assert_eq!(m.detector.status(), DetectionStatus::Running);
+        // Out of bounds edits are ignored
+        m.paint_cell(8, 0, 'B', &config);
+        assert_eq!(m.grid.get_cell(8, 0), Grid::EMPTY);
+    }
+
+    #[test]
+    fn initial_tape_is_laid_on_resize_and_counted() {
+        let mut config = Config::default();
+        config.simulation.initial_tape = "border".to_string();
+        config.simulation.cache_tape().unwrap();
+        let mut m = TuringMachine::new(1, "RL", &config);
+        m.update_grid_dimensions(6, 4, &config);
+        assert_eq!(m.grid.tape.values().filter(|&&c| c == 'B').count(), 16);
+        // Resets lay the cached tape, the file named in the config isn't read again
+        config.simulation.initial_tape = "missing.txt".to_string();
+        m.reset_clean(&config);
+        assert_eq!(m.grid.tape.values().filter(|&&c| c == 'B').count(), 16);
+        assert_eq!(m.stats.histogram().collect::<Vec<_>>(), vec![('B', 16)]);
+        let mut fresh = CycleDetector::new();
+        fresh.reset_with(&m.grid, &m.heads, m.steps);
+        assert_eq!(fresh.fingerprint(), m.detector.fingerprint());
+    }
+
     #[test]
     fn orbiting_head_cycles_with_period_4() {
         // Writes nothing and turns right forever, a pure 4-step orbit
         let (mut m, config) = test_machine(&[(
             (0, 'A'),
-            StateTransition { new_cell_state: 'A', turn_direction: TurnDirection::Right, new_internal_state: 0 },
+            StateTransition::new('A', TurnDirection::Right, 0),
         )]);
         for _ in 0..100 {
             m.step(8, 8, &config);
@@ -434,11 +1053,285 @@ mod tests {
         }
     }
 
+    #[test]
+    fn langtons_ant_builds_a_highway() {
+        let langton: Vec<_> = rules::parse_rules("RL").unwrap().into_iter().collect();
+        let (mut m, config) = test_machine(&langton);
+        for _ in 0..20_000 {
+            m.step(1000, 1000, &config);
+            if m.detector.status() != DetectionStatus::Running {
+                break;
+            }
+        }
+        match m.detector.status() {
+            DetectionStatus::Highway { period, displacement } => {
+                assert_eq!(period, 104);
+                assert_eq!((displacement.0.abs(), displacement.1.abs()), (2, 2));
+            }
+            other => panic!("expected highway, got {:?}", other),
+        }
+        // Not an outcome unless the config opts in
+        let reactions = ReactionsConfig::default();
+        assert_eq!(m.pending_outcome(&reactions), None);
+        m.highway_reaction = true;
+        assert_eq!(m.pending_outcome(&reactions), Some(Outcome::Highway));
+    }
+
+    #[test]
+    fn cycle_reports_its_transient() {
+        // Paints a 2x2 box of 'B' in 4 steps, then circles over it unchanged
+        let paint = StateTransition::new('B', TurnDirection::Right, 0);
+        let (mut m, config) = test_machine(&[((0, 'A'), paint.clone()), ((0, 'B'), paint)]);
+        for _ in 0..100 {
+            m.step(8, 8, &config);
+            if m.detector.status() != DetectionStatus::Running {
+                break;
+            }
+        }
+        // Measured off this thread, while the cycle itself is known at once
+        assert!(matches!(m.detector.status(), DetectionStatus::Cycle { period: 4, .. }));
+        m.wait_for_onset();
+        match m.detector.status() {
+            DetectionStatus::Cycle { period, mu, .. } => assert_eq!((mu, period), (Some(4), 4)),
+            other => panic!("expected cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pinned_runs_ignore_and_skip_the_state_files() {
+        let config = Config::default();
+        let mut m = TuringMachine::new(1, "RL", &config);
+        m.pin(Some("abc"), "LLRR");
+        // Pinned runs don't save on reset, so this leaves the state dir alone
+        m.reset(&config);
+        assert_eq!((m.current_seed.as_str(), m.rule_string.as_str()), ("abc", "LLRR"));
+        assert_eq!(m.cell_states, 4);
+
+        m.pin(None, "RL");
+        m.reset(&config);
+        assert!(!m.current_seed.is_empty());
+        let seed = m.current_seed.clone();
+        m.reset(&config);
+        assert_eq!(m.current_seed, seed);
+    }
+
+    #[test]
+    fn placing_a_second_head_under_a_random_schedule_stops_cycle_checks() {
+        let paint = StateTransition::new('B', TurnDirection::Right, 0);
+        let (mut m, mut config) = test_machine(&[((0, 'A'), paint.clone()), ((0, 'B'), paint)]);
+        config.simulation.schedule = HeadSchedule::Random;
+        // As spawn_heads reads it from config
+        m.random_schedule = true;
+        m.rebase_detection();
+        let run = |m: &mut TuringMachine| {
+            for _ in 0..200 {
+                m.step(16, 16, &config);
+                if m.detector.status() != DetectionStatus::Running {
+                    break;
+                }
+            }
+            m.detector.status()
+        };
+        // One head has no order to shuffle
+        assert!(matches!(run(&mut m), DetectionStatus::Cycle { period: 4, .. }));
+
+        m.place_head(12, 12, Direction::Left, &config);
+        assert_eq!(run(&mut m), DetectionStatus::Running);
+    }
+
+    // Same outcome whether checkpoints keep their tape or only the cells changed since
+    #[test]
+    fn bounded_memory_detection_matches_stored_snapshots() {
+        let paint = StateTransition::new('B', TurnDirection::Right, 0);
+        let flip = [
+            ((0, 'A'), StateTransition::new('B', TurnDirection::Right, 0)),
+            ((0, 'B'), StateTransition::new('A', TurnDirection::Right, 0)),
+        ];
+        // Budgets too small for the tape, the flip with room for the 4 cells it changes in a period.
+        // Langton's ant rewrites the whole torus each period, more than a change list can hold
+        let cases = [
+            (vec![((0, 'A'), StateTransition::new('A', TurnDirection::Right, 0))], 8, 0, true),
+            (vec![((0, 'A'), paint.clone()), ((0, 'B'), paint)], 8, 0, true),
+            (flip.to_vec(), 8, 4 * std::mem::size_of::<((i32, i32), (char, Option<u64>))>(), true),
+            (rules::parse_rules("RL").unwrap().into_iter().collect(), 6, 0, false),
+        ];
+        for (transitions, size, budget, provable) in cases {
+            let run = |budget: usize| {
+                let (mut m, config) = test_machine(&transitions);
+                // Cells the heads never reach, so every checkpoint is over the budget
+                for x in 0..size {
+                    m.grid.set_cell(x, 0, 'C', Color::White, None, false);
+                    m.grid.set_cell(x, size - 1, 'C', Color::White, None, false);
+                }
+                m.detector.set_memory_budget(budget);
+                m.reset_detection();
+                for _ in 0..50_000 {
+                    m.step(size, size, &config);
+                    if m.detector.status() != DetectionStatus::Running {
+                        break;
+                    }
+                }
+                m.detector.status()
+            };
+            let stored = run(usize::MAX);
+            assert!(matches!(stored, DetectionStatus::Cycle { .. }), "{:?}", stored);
+            assert_eq!(run(budget), if provable { stored } else { DetectionStatus::Running });
+        }
+    }
+
+    #[test]
+    fn cell_ages_are_kept_only_when_something_reads_them() {
+        let paint = StateTransition::new('B', TurnDirection::Right, 0);
+        let (mut m, mut config) = test_machine(&[((0, 'A'), paint)]);
+        m.step(8, 8, &config);
+        assert!(m.grid.written_at.is_empty());
+        config.display.cell_age_fade = 100;
+        m.step(8, 8, &config);
+        assert_eq!(m.grid.written_at.len(), 1);
+    }
+
+    #[test]
+    fn visits_are_counted_only_while_the_heatmap_is_shown() {
+        let (mut m, config) = test_machine(&[((0, 'A'), StateTransition::new('A', TurnDirection::Right, 0))]);
+        m.grid_width = 8;
+        m.grid_height = 8;
+        m.step(8, 8, &config);
+        assert!(m.grid.visits.is_empty());
+
+        // Turning right on blank tape circles four cells
+        m.grid.count_visits(true);
+        for _ in 0..8 {
+            m.step(8, 8, &config);
+        }
+        assert_eq!((m.grid.visits.len(), m.grid.max_visits()), (4, 2));
+        let text = m.heatmap_text();
+        assert_eq!(text.chars().filter(|&c| c == '9').count(), 4);
+        assert_eq!(text.lines().count(), 8);
+
+        m.grid.count_visits(false);
+        assert_eq!((m.grid.visits.len(), m.grid.max_visits()), (0, 0));
+    }
+
+    #[test]
+    fn replays_rebuild_the_run_from_its_origin() {
+        let coin = StateTransition {
+            turn_choices: vec![(TurnDirection::Right, 1.0), (TurnDirection::Left, 1.0)],
+            ..StateTransition::new('B', TurnDirection::Right, 0)
+        };
+        let (mut m, config) = test_machine(&[((0, 'A'), coin.clone()), ((0, 'B'), coin)]);
+        m.rng = StdRng::seed_from_u64(7);
+        m.reset_detection();
+        for _ in 0..200 {
+            m.step(8, 8, &config);
+        }
+        // Same draws as the live run, so the same tape and heads
+        let mut replay = m.replay_from_origin();
+        while replay.steps < m.steps {
+            replay.step(8, 8, &config);
+        }
+        assert!(detection::same_state(&replay.grid, &replay.heads, &m.grid, &m.heads, None));
+        // Nothing of the live run's history comes along
+        let fresh = m.replay_from_origin();
+        assert_eq!((fresh.steps, fresh.grid.visits.len(), fresh.stats.visited()), (0, 0, 0));
+    }
+
+    #[test]
+    fn langtons_ant_goes_from_chaos_to_linear_growth() {
+        let langton: Vec<_> = rules::parse_rules("RL").unwrap().into_iter().collect();
+        let (mut m, config) = test_machine(&langton);
+        // Highway detection would otherwise stop the stats from being interesting
+        m.detector.set_cycle_detection(false);
+        m.detector.set_highway_detection(false);
+        let mut seen = Vec::new();
+        for _ in 0..16_000 {
+            m.step(1000, 1000, &config);
+            if m.steps % 2000 == 0 {
+                seen.push(m.behaviour.behaviour());
+            }
+        }
+        assert_eq!(seen[0], Behaviour::Warming);
+        assert!(seen[1..5].iter().all(|&b| b == Behaviour::Chaotic), "{:?}", seen);
+        assert_eq!(seen.last(), Some(&Behaviour::LinearGrowth), "{:?}", seen);
+    }
+
+    #[test]
+    fn timeout_is_an_outcome_and_snapshots_show_the_tape() {
+        let (mut m, config) = test_machine(&[(
+            (0, 'A'),
+            StateTransition::new('B', TurnDirection::Right, 0),
+        )]);
+        let reactions = ReactionsConfig { max_steps: 2, ..Default::default() };
+        m.grid_width = 6;
+        m.grid_height = 6;
+        m.step(6, 6, &config);
+        assert_eq!(m.pending_outcome(&reactions), None);
+        m.step(6, 6, &config);
+        assert_eq!(m.pending_outcome(&reactions), Some(Outcome::Timeout));
+
+        let text = m.snapshot_text();
+        assert!(text.starts_with("# rule: RL\n"), "{}", text);
+        let rows: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[4], "....BB");
+
+        // Glyph drawings pad single-width glyphs to the two columns a cell takes
+        let mut config = config;
+        config.display.state_chars = vec!["x".to_string(), "中".to_string(), "#".to_string()];
+        config.display.cache_char_data();
+        assert!(config.display.get_state_char_data('B').is_wide);
+        let glyphs = m.glyph_text(&config);
+        let rows: Vec<&str> = glyphs.lines().collect();
+        assert_eq!(rows[4], "        中中");
+        assert_eq!(rows[0], "");
+    }
+
+    #[test]
+    fn orbiting_head_is_flagged_and_respawned() {
+        let (mut m, mut config) = test_machine(&[(
+            (0, 'A'),
+            StateTransition::new('A', TurnDirection::Right, 0),
+        )]);
+        for _ in 0..256 {
+            m.step(40, 40, &config);
+        }
+        assert_eq!(m.heads[0].watch.activity(), HeadActivity::Orbiting);
+
+        config.simulation.respawn_stuck_heads = true;
+        m.step(40, 40, &config);
+        assert_eq!(m.heads[0].watch.activity(), HeadActivity::Active);
+        assert!(m.heads[0].trail.is_empty());
+    }
+
+    #[test]
+    fn respawned_heads_leave_their_spot_for_a_free_one() {
+        let (mut m, _) = test_machine(&[((0, 'A'), StateTransition::new('A', TurnDirection::None, 0))]);
+        m.heads.push(m.heads[0].clone());
+        (m.heads[0].x, m.heads[0].y, m.heads[1].x, m.heads[1].y) = (0, 0, 1, 1);
+        // Two of the four cells are taken, so a draw lands on a head half the time
+        for _ in 0..20 {
+            let before: Vec<_> = m.heads.iter().map(|head| (head.x, head.y)).collect();
+            for head in &mut m.heads {
+                (0..16).for_each(|_| head.watch.on_no_rule());
+            }
+            m.respawn_stuck_heads(2, 2);
+            let after: Vec<_> = m.heads.iter().map(|head| (head.x, head.y)).collect();
+            assert!(before.iter().zip(&after).all(|(old, new)| old != new));
+            assert_ne!(after[0], after[1]);
+        }
+
+        // With no cell free the head stays
+        let (mut m, _) = test_machine(&[((0, 'A'), StateTransition::new('A', TurnDirection::None, 0))]);
+        (m.heads[0].x, m.heads[0].y) = (0, 0);
+        (0..16).for_each(|_| m.heads[0].watch.on_no_rule());
+        m.respawn_stuck_heads(1, 1);
+        assert_eq!((m.heads[0].x, m.heads[0].y), (0, 0));
+    }
+
     #[test]
     fn reset_returns_detector_to_running() {
         let (mut m, config) = test_machine(&[(
             (0, 'A'),
-            StateTransition { new_cell_state: 'B', turn_direction: TurnDirection::Right, new_internal_state: 0 },
+            StateTransition::new('B', TurnDirection::Right, 0),
         )]);
         for _ in 0..100 {
             m.step(8, 8, &config);
@@ -449,4 +1342,88 @@ mod tests {
         assert_eq!(m.detector.status(), DetectionStatus::Running);
         assert!(!m.has_looped, "new run must clear the proven-loop flag");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn stochastic_runs_replay_from_seed_and_never_claim_cycles() {
+        let coin = StateTransition {
+            turn_choices: vec![(TurnDirection::Right, 1.0), (TurnDirection::Left, 1.0)],
+            ..StateTransition::new('A', TurnDirection::Right, 0)
+        };
+        let run = |seed| {
+            let (mut m, config) = test_machine(&[((0, 'A'), coin.clone())]);
+            m.rng = StdRng::seed_from_u64(seed);
+            let mut path = Vec::new();
+            for _ in 0..200 {
+                m.step(8, 8, &config);
+                path.push((m.heads[0].x, m.heads[0].y));
+            }
+            (path, m.detector.status())
+        };
+        let (path, status) = run(3);
+        assert_eq!(path, run(3).0);
+        assert_ne!(path, run(4).0);
+        // A deterministic twin of this walk would prove a cycle within a few steps
+        assert_eq!(status, DetectionStatus::Running);
+    }
+
+    #[test]
+    fn jumps_and_in_place_turns_keep_the_trail_honest() {
+        let mut jump = StateTransition::new('B', TurnDirection::None, 0);
+        jump.movement = Movement { forward: 2, side: 1 };
+        let (mut m, config) = test_machine(&[((0, 'A'), jump)]);
+        m.step(16, 16, &config);
+        assert_eq!((m.heads[0].x, m.heads[0].y), (5, 2));
+        assert_eq!(m.heads[0].trail.iter().copied().collect::<Vec<_>>(), vec![(4, 4)]);
+
+        let mut spin = StateTransition::new('A', TurnDirection::Right, 0);
+        spin.movement = Movement { forward: 0, side: 0 };
+        spin.writes = false;
+        let (mut m, config) = test_machine(&[((0, 'A'), spin)]);
+        m.step(16, 16, &config);
+        assert_eq!((m.heads[0].x, m.heads[0].y, m.heads[0].direction), (4, 4, Direction::Right));
+        assert!(m.heads[0].trail.is_empty());
+        assert!(m.tape().is_empty());
+
+        let mut stride = StateTransition::new('B', TurnDirection::None, 0);
+        stride.movement = Movement { forward: 3, side: 0 };
+        let (mut m, config) = test_machine(&[((0, 'A'), stride)]);
+        m.step(16, 16, &config);
+        assert_eq!(m.heads[0].trail.iter().copied().collect::<Vec<_>>(), vec![(4, 4), (4, 3), (4, 2)]);
+        assert_eq!(m.heads[0].y, 1);
+    }
+
+    #[test]
+    fn slower_heads_sit_out_their_off_steps() {
+        let (mut m, mut config) = test_machine(&[(
+            (0, 'A'),
+            StateTransition::new('A', TurnDirection::None, 0),
+        )]);
+        let mut slow = m.heads[0].clone();
+        slow.x = 10;
+        m.heads.push(slow);
+        config.simulation.head_speeds = vec![1, 3];
+        for _ in 0..6 {
+            m.step(16, 16, &config);
+        }
+        assert_eq!(m.heads[0].y, 14); // 4 - 6, wrapped
+        assert_eq!(m.heads[1].y, 2);
+    }
+
+    #[test]
+    fn lockstep_heads_read_before_anyone_writes() {
+        // Two heads share a cell: in sequential order the second sees the first's 'B'
+        let rules = [
+            ((0, 'A'), StateTransition::new('B', TurnDirection::Left, 0)),
+            ((0, 'B'), StateTransition::new('A', TurnDirection::Right, 0)),
+        ];
+        let run = |schedule| {
+            let (mut m, mut config) = test_machine(&rules);
+            m.heads.push(m.heads[0].clone());
+            config.simulation.schedule = schedule;
+            m.step(16, 16, &config);
+            (m.heads[0].direction, m.heads[1].direction)
+        };
+        assert_eq!(run(HeadSchedule::Sequential), (Direction::Left, Direction::Right));
+        assert_eq!(run(HeadSchedule::Lockstep), (Direction::Left, Direction::Left));
+    }
+}
@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
+use rand::Rng;
+use super::golly;
 
 #[inline]
-fn state_char(index: usize) -> char {
+pub fn state_char(index: usize) -> char {
     if index < 26 {
         (b'A' + index as u8) as char
     } else if index < 52 {
@@ -12,6 +14,15 @@ fn state_char(index: usize) -> char {
     }
 }
 
+#[inline]
+pub fn state_index(c: char) -> Option<usize> {
+    match c {
+        'A'..='Z' => Some(c as usize - 'A' as usize),
+        'a'..='z' => Some(c as usize - 'a' as usize + 26),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Direction {
     Up,
@@ -39,6 +50,22 @@ impl Direction {
         }
     }
 
+    #[inline(always)]
+    pub fn delta(&self) -> (i32, i32) {
+        self.apply(0, 0)
+    }
+
+    // Moves forward along this heading, then sideways to its right
+    #[inline]
+    pub fn advance(&self, x: i32, y: i32, movement: Movement) -> (i32, i32) {
+        let (fx, fy) = self.delta();
+        let (rx, ry) = self.turn_right().delta();
+        (
+            x + fx * movement.forward + rx * movement.side,
+            y + fy * movement.forward + ry * movement.side,
+        )
+    }
+
     #[inline]
     pub fn turn_left(self) -> Self {
         match self {
@@ -82,11 +109,79 @@ impl Direction {
     }
 }
 
+// Weighted alternatives of a stochastic transition
+pub type TurnChoices = Vec<(TurnDirection, f64)>;
+
+// Longest move a single transition may make along either axis
+pub const MAX_MOVE: i32 = 16;
+
+// Distance travelled after turning, relative to the new heading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Movement {
+    pub forward: i32,
+    pub side: i32,      // positive is to the right
+}
+
+impl Movement {
+    pub const STEP: Movement = Movement { forward: 1, side: 0 };
+
+    // Jumps leave the cells in between untouched
+    #[inline]
+    pub fn is_jump(&self) -> bool {
+        self.side != 0
+    }
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self::STEP
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StateTransition {
     pub new_cell_state: char,
     pub turn_direction: TurnDirection,
     pub new_internal_state: usize,
+    // Weighted alternatives, empty for deterministic transitions
+    pub turn_choices: TurnChoices,
+    pub movement: Movement,
+    // False leaves the cell under the head as it was
+    pub writes: bool,
+}
+
+impl StateTransition {
+    pub fn new(new_cell_state: char, turn_direction: TurnDirection, new_internal_state: usize) -> Self {
+        Self {
+            new_cell_state,
+            turn_direction,
+            new_internal_state,
+            turn_choices: Vec::new(),
+            movement: Movement::STEP,
+            writes: true,
+        }
+    }
+
+    #[inline]
+    pub fn is_stochastic(&self) -> bool {
+        !self.turn_choices.is_empty()
+    }
+
+    // Draw a turn from the weighted alternatives, or the fixed turn
+    pub fn pick_turn(&self, rng: &mut impl Rng) -> TurnDirection {
+        if self.turn_choices.is_empty() {
+            return self.turn_direction;
+        }
+        let total: f64 = self.turn_choices.iter().map(|&(_, w)| w).sum();
+        let mut roll = rng.random::<f64>() * total;
+        for &(turn, weight) in &self.turn_choices {
+            if roll < weight {
+                return turn;
+            }
+            roll -= weight;
+        }
+        self.turn_choices[self.turn_choices.len() - 1].0
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -110,21 +205,204 @@ impl TurnDirection {
     }
 }
 
-pub fn parse_rules(rule_string: &str) -> BTreeMap<(usize, char), StateTransition> {
+#[inline]
+fn turn_from_char(c: char) -> TurnDirection {
+    match c {
+        'L' => TurnDirection::Left,
+        'R' => TurnDirection::Right,
+        'U' => TurnDirection::UTurn,
+        'D' => TurnDirection::None,
+        'N' => TurnDirection::Absolute(Direction::Up),
+        'S' => TurnDirection::Absolute(Direction::Down),
+        'E' => TurnDirection::Absolute(Direction::Right),
+        'W' => TurnDirection::Absolute(Direction::Left),
+        _ => TurnDirection::Right,
+    }
+}
+
+fn turn_from_token(token: &str) -> Option<TurnDirection> {
+    match token {
+        "NW" => Some(TurnDirection::Absolute(Direction::UpLeft)),
+        "NE" => Some(TurnDirection::Absolute(Direction::UpRight)),
+        "SW" => Some(TurnDirection::Absolute(Direction::DownLeft)),
+        "SE" => Some(TurnDirection::Absolute(Direction::DownRight)),
+        "L" | "R" | "U" | "D" | "N" | "S" | "E" | "W" => token.chars().next().map(turn_from_char),
+        _ => None,
+    }
+}
+
+// Parses the inside of a weighted group like "R:0.9|L:0.1", a bare direction weighs 1
+pub fn parse_weighted_group(group: &str) -> Result<TurnChoices, String> {
+    let mut choices = Vec::new();
+    for alternative in group.split('|') {
+        let (token, weight) = match alternative.split_once(':') {
+            Some((token, weight)) => {
+                let weight = weight.trim().parse::<f64>()
+                    .map_err(|_| format!("invalid weight '{}' in '[{}]'", weight, group))?;
+                (token.trim(), weight)
+            }
+            None => (alternative.trim(), 1.0),
+        };
+        let turn = turn_from_token(token)
+            .ok_or_else(|| format!("invalid direction '{}' in '[{}]'", token, group))?;
+        if !weight.is_finite() || weight <= 0.0 {
+            return Err(format!("weight for '{}' must be positive in '[{}]'", token, group));
+        }
+        choices.push((turn, weight));
+    }
+    Ok(choices)
+}
+
+// Parses "2", "0" or "2+1"/"2-1" from inside a movement suffix like R(2+1)
+pub fn parse_movement(inner: &str) -> Result<Movement, String> {
+    let inner = inner.trim();
+    let (forward, side) = match inner.find(['+', '-']) {
+        Some(pos) => {
+            let side = inner[pos + 1..].trim().parse::<i32>()
+                .map_err(|_| format!("invalid sideways distance in '({})'", inner))?;
+            let side = if inner[pos..].starts_with('-') { -side } else { side };
+            (&inner[..pos], side)
+        }
+        None => (inner, 0),
+    };
+    let forward = forward.trim().parse::<i32>()
+        .map_err(|_| format!("invalid step length in '({})'", inner))?;
+    if !(0..=MAX_MOVE).contains(&forward) || side.abs() > MAX_MOVE {
+        return Err(format!("move '({})' is out of range (0-{})", inner, MAX_MOVE));
+    }
+    Ok(Movement { forward, side })
+}
+
+// A turn token with its suffixes, before the written cell is known
+struct ParsedTurn {
+    turn: TurnDirection,
+    choices: TurnChoices,
+    movement: Movement,
+    writes: bool,
+    consumed: usize,
+}
+
+impl ParsedTurn {
+    fn into_transition(self, new_cell_state: char, new_internal_state: usize) -> StateTransition {
+        StateTransition {
+            turn_choices: self.choices,
+            movement: self.movement,
+            writes: self.writes,
+            ..StateTransition::new(new_cell_state, self.turn, new_internal_state)
+        }
+    }
+}
+
+// Reads an optional (n)/(f+s) move and ~ no-write flag, returns them with their byte length
+fn parse_suffixes(s: &str) -> Result<(Movement, bool, usize), String> {
+    let mut movement = Movement::STEP;
+    let mut consumed = 0;
+    if s.starts_with('(') {
+        let end = s.find(')').ok_or_else(|| format!("unclosed '(' in '{}'", s))?;
+        movement = parse_movement(&s[1..end])?;
+        consumed = end + 1;
+    }
+    let writes = !s[consumed..].starts_with('~');
+    if !writes {
+        consumed += 1;
+    }
+    Ok((movement, writes, consumed))
+}
+
+// Parses one turn with its suffixes at the start of `remaining`, None when nothing is left
+fn parse_turn(remaining: &str) -> Result<Option<ParsedTurn>, String> {
+    let (turn, choices, consumed) = if remaining.starts_with('[') {
+        let end = remaining.find(']').ok_or_else(|| format!("unclosed '[' in '{}'", remaining))?;
+        let choices = parse_weighted_group(&remaining[1..end])?;
+        // Most likely alternative stands in where a single turn is needed
+        let likeliest = choices.iter()
+            .fold(choices[0], |best, &c| if c.1 > best.1 { c } else { best });
+        (likeliest.0, choices, end + 1)
+    } else if remaining.starts_with("NW") || remaining.starts_with("NE") ||
+    remaining.starts_with("SW") || remaining.starts_with("SE") {
+        let Some(turn) = turn_from_token(&remaining[..2]) else {
+            return Ok(None);
+        };
+        (turn, Vec::new(), 2)
+    } else {
+        let Some(c) = remaining.chars().next() else {
+            return Ok(None);
+        };
+        (turn_from_char(c), Vec::new(), c.len_utf8())
+    };
+    let (movement, writes, suffix) = parse_suffixes(&remaining[consumed..])?;
+    Ok(Some(ParsedTurn { turn, choices, movement, writes, consumed: consumed + suffix }))
+}
+
+// Byte offsets of each rule position, a weighted group or suffix belongs to its turn
+fn token_positions(s: &str) -> Result<Vec<usize>, String> {
+    let mut positions = Vec::new();
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        positions.push(i);
+        i += if c == '[' {
+            s[i..].find(']').map_or(s.len() - i, |end| end + 1)
+        } else {
+            c.len_utf8()
+        };
+        i += parse_suffixes(&s[i..])?.2;
+    }
+    Ok(positions)
+}
+
+// Splits on `sep`, ignoring separators inside weighted groups
+pub fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            c if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+pub fn is_stochastic(rules: &BTreeMap<(usize, char), StateTransition>) -> bool {
+    rules.values().any(|t| t.is_stochastic())
+}
+
+// Cell states a rule writes, counted from 'A' up to the highest one. String rules
+// read every state, so what they read says nothing about how many show up
+pub fn cell_state_count(rules: &BTreeMap<(usize, char), StateTransition>) -> usize {
+    rules.values()
+        .filter_map(|t| state_index(t.new_cell_state))
+        .max()
+        .map_or(1, |index| index + 1)
+}
+
+pub fn parse_rules(rule_string: &str) -> Result<BTreeMap<(usize, char), StateTransition>, String> {
     let mut rules = BTreeMap::new();
+
+    // Golly names are read through the notation they translate to
+    if golly::is_golly_name(rule_string) {
+        return parse_rules(&golly::to_rule_string(rule_string)?);
+    }
     
     // Check for standard notation
     if rule_string.trim().starts_with('{') {
         if parse_brace_notation(rule_string, &mut rules).is_err() {
-            parse_fallback_rules(rule_string, &mut rules);
+            parse_fallback_rules(rule_string, &mut rules)?;
         }
-    } else if rule_string.contains('>') || rule_string.contains(':') {
-        parse_state_transition_rules(rule_string, &mut rules);
+    } else if rule_string.contains('>') || split_top_level(rule_string, ':').len() > 1 {
+        parse_state_transition_rules(rule_string, &mut rules)?;
     } else {
-        parse_string_rules(rule_string, &mut rules);
+        parse_string_rules(rule_string, &mut rules)?;
     }
     
-    rules
+    Ok(rules)
 }
 
 fn parse_brace_notation(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) -> Result<(), String> {
@@ -172,11 +450,11 @@ fn parse_brace_notation(rule_string: &str, rules: &mut BTreeMap<(usize, char), S
                 _ => return Err(format!("Invalid turn direction flag: {}. Must be 1, 2, 4, or 8", turn_direction_flag)),
             };
             
-            rules.insert((state_idx, state_char(cell_idx)), StateTransition {
-                new_cell_state: state_char(new_cell_state_idx),
+            rules.insert((state_idx, state_char(cell_idx)), StateTransition::new(
+                state_char(new_cell_state_idx),
                 turn_direction,
                 new_internal_state,
-            });
+            ));
         }
     }
     
@@ -225,63 +503,42 @@ fn split_by_top_level_comma(s: &str) -> Result<Vec<String>, String> {
     Ok(parts)
 }
 
-fn parse_fallback_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) {
-    if rule_string.contains('>') || rule_string.contains(':') {
-        parse_state_transition_rules(rule_string, rules);
+fn parse_fallback_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) -> Result<(), String> {
+    if rule_string.contains('>') || split_top_level(rule_string, ':').len() > 1 {
+        parse_state_transition_rules(rule_string, rules)
     } else {
-        parse_string_rules(rule_string, rules);
+        parse_string_rules(rule_string, rules)
     }
 }
 
-fn parse_state_transition_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) {
-    let state_rules: Vec<&str> = rule_string.split(':').collect();
+fn parse_state_transition_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) -> Result<(), String> {
+    let state_rules: Vec<&str> = split_top_level(rule_string, ':');
     
     for (state_idx, state_rule) in state_rules.iter().enumerate() {
-        parse_state_rule(state_idx, state_rule, &state_rules, rules);
+        parse_state_rule(state_idx, state_rule, &state_rules, rules)?;
     }
+    Ok(())
 }
 
-fn parse_string_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) {
+fn parse_string_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), StateTransition>) -> Result<(), String> {
     if rule_string.is_empty() {
-        return; // Don't parse empty rules
+        return Ok(()); // Don't parse empty rules
     }
 
+    let positions = token_positions(rule_string)?;
     let mut state_index = 0;
     
     loop {
-        let rule_char_index = state_index % rule_string.len();
-        let rule_chars: Vec<char> = rule_string.chars().collect();
-        let c = rule_chars[rule_char_index];
+        let rule_char_index = state_index % positions.len();
+        let remaining = &rule_string[positions[rule_char_index]..];
         
-        let remaining = &rule_string[rule_char_index..];
-        
-        let turn_direction = if remaining.starts_with("NW") {
-            TurnDirection::Absolute(Direction::UpLeft)
-        } else if remaining.starts_with("NE") {
-            TurnDirection::Absolute(Direction::UpRight)
-        } else if remaining.starts_with("SW") {
-            TurnDirection::Absolute(Direction::DownLeft)
-        } else if remaining.starts_with("SE") {
-            TurnDirection::Absolute(Direction::DownRight)
-        } else {
-            match c {
-                'L' => TurnDirection::Left,
-                'R' => TurnDirection::Right,
-                'U' => TurnDirection::UTurn,
-                'D' => TurnDirection::None,
-                'N' => TurnDirection::Absolute(Direction::Up),
-                'S' => TurnDirection::Absolute(Direction::Down),
-                'E' => TurnDirection::Absolute(Direction::Right),
-                'W' => TurnDirection::Absolute(Direction::Left),
-                _ => TurnDirection::Right,
-            }
+        let next_cell = state_char((state_index + 1) % positions.len());
+        let transition = match parse_turn(remaining)? {
+            Some(parsed) => parsed.into_transition(next_cell, 0),
+            None => StateTransition::new(next_cell, TurnDirection::Right, 0),
         };
         
-        rules.insert((0, state_char(state_index)), StateTransition {
-            new_cell_state: state_char((state_index + 1) % rule_string.len()),
-            turn_direction,
-            new_internal_state: 0,
-        });
+        rules.insert((0, state_char(state_index)), transition);
         
         state_index += 1;
         
@@ -289,6 +546,47 @@ fn parse_string_rules(rule_string: &str, rules: &mut BTreeMap<(usize, char), Sta
             break;
         }
     }
+    Ok(())
+}
+
+// One explicit transition like "L1>1", defaults depend on where it sits
+pub fn parse_transition(transition: &str, state_idx: usize, cell_idx: usize) -> Result<Option<StateTransition>, String> {
+    // Parse direction and cell specification
+    let (directions, next_state) = if let Some(transition_pos) = transition.find('>') {
+        let directions = &transition[..transition_pos];
+        let next_state_str = &transition[transition_pos + 1..];
+        let next_state = next_state_str.parse::<usize>().unwrap_or(state_idx);
+        (directions, next_state)
+    } else {
+        (transition, state_idx)
+    };
+    
+    // Check if direction string ends with a cell specifier
+    let (direction_part, next_cell) = if let Some(last_char) = directions.chars().last() {
+        if last_char.is_ascii_digit() {
+            let cell_idx = last_char.to_digit(10).unwrap_or(0) as usize;
+            let next_cell = state_char(cell_idx);
+            let direction_part = &directions[..directions.len() - 1];
+            (direction_part, next_cell)
+        } else {
+            (directions, state_char((cell_idx + 1) % 2))
+        }
+    } else {
+        (directions, state_char((cell_idx + 1) % 2))
+    };
+    
+    // Only the first letter counts here, weighted groups are taken whole
+    let turn = if direction_part.starts_with('[') {
+        parse_turn(direction_part)?
+    } else {
+        direction_part.chars().next().map(|c| {
+            let rest = direction_part.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+            let (movement, writes, _) = parse_suffixes(rest)?;
+            Ok::<_, String>(ParsedTurn { turn: turn_from_char(c), choices: Vec::new(), movement, writes, consumed: 0 })
+        }).transpose()?
+    };
+    
+    Ok(turn.map(|parsed| parsed.into_transition(next_cell, next_state)))
 }
 
 fn parse_state_rule(
@@ -296,58 +594,16 @@ fn parse_state_rule(
     rule: &str, 
     all_state_rules: &[&str], 
     rules: &mut BTreeMap<(usize, char), StateTransition>
-) {
+) -> Result<(), String> {
     // Handle internal multi-state
     if rule.contains(',') {
         let transitions: Vec<&str> = rule.split(',').collect();
         for (cell_idx, transition) in transitions.iter().enumerate() {
-            // Parse direction and cell specification
-            let (directions, next_state) = if let Some(transition_pos) = transition.find('>') {
-                let directions = &transition[..transition_pos];
-                let next_state_str = &transition[transition_pos + 1..];
-                let next_state = next_state_str.parse::<usize>().unwrap_or(state_idx);
-                (directions, next_state)
-            } else {
-                (*transition, state_idx)
-            };
-            
-            let current_cell = state_char(cell_idx);
-            
-            // Check if direction string ends with a cell specifier
-            let (direction_part, next_cell) = if let Some(last_char) = directions.chars().last() {
-                if last_char.is_ascii_digit() {
-                    let cell_idx = last_char.to_digit(10).unwrap_or(0) as usize;
-                    let next_cell = state_char(cell_idx);
-                    let direction_part = &directions[..directions.len() - 1];
-                    (direction_part, next_cell)
-                } else {
-                    (directions, state_char((cell_idx + 1) % 2))
-                }
-            } else {
-                (directions, state_char((cell_idx + 1) % 2))
-            };
-            
-            if let Some(direction_char) = direction_part.chars().next() {
-                let turn_direction = match direction_char {
-                    'L' => TurnDirection::Left,
-                    'R' => TurnDirection::Right,
-                    'U' => TurnDirection::UTurn,
-                    'D' => TurnDirection::None,
-                    'N' => TurnDirection::Absolute(Direction::Up),
-                    'S' => TurnDirection::Absolute(Direction::Down),
-                    'E' => TurnDirection::Absolute(Direction::Right),
-                    'W' => TurnDirection::Absolute(Direction::Left),
-                    _ => TurnDirection::Right,
-                };
-                
-                rules.insert((state_idx, current_cell), StateTransition {
-                    new_cell_state: next_cell,
-                    turn_direction,
-                    new_internal_state: next_state,
-                });
+            if let Some(parsed) = parse_transition(transition, state_idx, cell_idx)? {
+                rules.insert((state_idx, state_char(cell_idx)), parsed);
             }
         }
-        return;
+        return Ok(());
     }
 
     // Check if rule has state transition indicator
@@ -367,47 +623,125 @@ fn parse_state_rule(
         (rule, next_state)
     };
     
+    let cycle_length = token_positions(directions)?.len();
     let mut i = 0;
     let mut cell_state_idx = 0;
     
     while i < directions.len() && cell_state_idx < 256 {
         let remaining = &directions[i..];
         let current_cell = state_char(cell_state_idx);
-        let next_cell = state_char((cell_state_idx + 1) % directions.len());
+        let next_cell = state_char((cell_state_idx + 1) % cycle_length);
         
         // Parse direction/turn
-        let (turn_direction, chars_consumed) = if remaining.starts_with("NW") {
-            (TurnDirection::Absolute(Direction::UpLeft), 2)
-        } else if remaining.starts_with("NE") {
-            (TurnDirection::Absolute(Direction::UpRight), 2)
-        } else if remaining.starts_with("SW") {
-            (TurnDirection::Absolute(Direction::DownLeft), 2)
-        } else if remaining.starts_with("SE") {
-            (TurnDirection::Absolute(Direction::DownRight), 2)
-        } else if let Some(c) = remaining.chars().next() {
-            let turn = match c {
-                'L' => TurnDirection::Left,
-                'R' => TurnDirection::Right,
-                'U' => TurnDirection::UTurn,
-                'D' => TurnDirection::None,
-                'N' => TurnDirection::Absolute(Direction::Up),
-                'S' => TurnDirection::Absolute(Direction::Down),
-                'E' => TurnDirection::Absolute(Direction::Right),
-                'W' => TurnDirection::Absolute(Direction::Left),
-                _ => TurnDirection::Right,
-            };
-            (turn, 1)
-        } else {
+        let Some(parsed) = parse_turn(remaining)? else {
             break;
         };
+        let chars_consumed = parsed.consumed;
         
-        rules.insert((state_idx, current_cell), StateTransition {
-            new_cell_state: next_cell,
-            turn_direction,
-            new_internal_state: next_state,
-        });
+        rules.insert((state_idx, current_cell), parsed.into_transition(next_cell, next_state));
         
         i += chars_consumed;
         cell_state_idx += 1;
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn weighted_group_parses_as_one_position() {
+        let rules = parse_rules("[R:0.9|L:0.1]L").unwrap();
+        let first = &rules[&(0, 'A')];
+        assert_eq!(first.turn_direction, TurnDirection::Right);
+        assert_eq!(first.turn_choices, vec![(TurnDirection::Right, 0.9), (TurnDirection::Left, 0.1)]);
+        assert_eq!(first.new_cell_state, 'B');
+        let second = &rules[&(0, 'B')];
+        assert!(!second.is_stochastic());
+        assert_eq!(second.new_cell_state, 'A');
+    }
+
+    #[test]
+    fn weighted_group_colons_do_not_split_states() {
+        let rules = parse_rules("[R:1|L:3]L:RR").unwrap();
+        assert_eq!(rules[&(0, 'A')].turn_direction, TurnDirection::Left);
+        assert_eq!(rules[&(0, 'A')].new_internal_state, 1);
+        assert!(!rules[&(1, 'A')].is_stochastic());
+        assert!(is_stochastic(&rules));
+
+        let explicit = parse_rules("[N|S]1>1,L0>0").unwrap();
+        assert_eq!(explicit[&(0, 'A')].turn_choices.len(), 2);
+        assert_eq!(explicit[&(0, 'A')].new_cell_state, 'B');
+        assert_eq!(explicit[&(0, 'A')].new_internal_state, 1);
+        assert!(!is_stochastic(&parse_rules("R1>1,L0>0").unwrap()));
+    }
+
+    #[test]
+    fn state_count_follows_the_written_states() {
+        assert_eq!(cell_state_count(&parse_rules("RL").unwrap()), 2);
+        assert_eq!(cell_state_count(&parse_rules("LLRR").unwrap()), 4);
+        assert_eq!(cell_state_count(&BTreeMap::new()), 1);
+        // Lowercase states come after 'Z'
+        let lowercase = BTreeMap::from([((0, 'A'), StateTransition::new('c', TurnDirection::Right, 0))]);
+        assert_eq!(cell_state_count(&lowercase), 29);
+    }
+
+    #[test]
+    fn weighted_group_rejects_bad_entries() {
+        assert!(parse_weighted_group("R:0.5|X:0.5").is_err());
+        assert!(parse_weighted_group("R:0|L:1").is_err());
+        assert!(parse_weighted_group("R:abc").is_err());
+        assert_eq!(parse_weighted_group("NW|SE:2").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn pick_turn_follows_weights_and_seed() {
+        let transition = StateTransition {
+            turn_choices: vec![(TurnDirection::Right, 0.9), (TurnDirection::Left, 0.1)],
+            ..StateTransition::new('B', TurnDirection::Right, 0)
+        };
+        let draw = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..1000).map(|_| transition.pick_turn(&mut rng)).collect::<Vec<_>>()
+        };
+        let picks = draw(7);
+        assert_eq!(picks, draw(7));
+        let rights = picks.iter().filter(|&&t| t == TurnDirection::Right).count();
+        assert!((850..=950).contains(&rights), "{rights}");
+    }
+
+    #[test]
+    fn move_suffixes_attach_to_their_turn() {
+        let rules = parse_rules("R(0)L(2)~D(2+1)").unwrap();
+        assert_eq!(rules[&(0, 'A')].movement, Movement { forward: 0, side: 0 });
+        assert_eq!(rules[&(0, 'B')].movement, Movement { forward: 2, side: 0 });
+        assert!(!rules[&(0, 'B')].writes);
+        assert_eq!(rules[&(0, 'C')].movement, Movement { forward: 2, side: 1 });
+        assert_eq!(rules[&(0, 'C')].new_cell_state, 'A');
+        assert!(rules[&(0, 'A')].writes);
+
+        let explicit = parse_rules("L(3)1>1,R~>0").unwrap();
+        assert_eq!(explicit[&(0, 'A')].movement.forward, 3);
+        assert_eq!(explicit[&(0, 'A')].new_cell_state, 'B');
+        assert!(!explicit[&(0, 'B')].writes);
+
+        // Bad suffixes are errors rather than single steps
+        assert!(parse_rules("R(x)L").is_err());
+        assert!(parse_rules("L(17)1>1,R0>0").is_err());
+        assert!(parse_rules("R(2L").is_err());
+    }
+
+    #[test]
+    fn movement_parses_and_rejects_out_of_range() {
+        assert_eq!(parse_movement("2-1"), Ok(Movement { forward: 2, side: -1 }));
+        assert_eq!(parse_movement("0"), Ok(Movement { forward: 0, side: 0 }));
+        assert!(parse_movement("17").is_err());
+        assert!(parse_movement("x").is_err());
+        // Knight's move heading up: two up, one right
+        assert_eq!(Direction::Up.advance(5, 5, Movement { forward: 2, side: 1 }), (6, 3));
+        assert_eq!(Direction::Left.advance(5, 5, Movement { forward: 2, side: 1 }), (3, 4));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use super::grid::Grid;
+use super::rules::{self, Direction, StateTransition, state_char, state_index};
+
+// Upper bound on states x cells x readings a sensor table may address
+pub const MAX_SENSOR_KEYS: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sensor {
+    Ahead,          // state of the cell in front of the head
+    Moore,          // non-blank cells among the 8 neighbours
+    VonNeumann,     // non-blank cells among the 4 orthogonal neighbours
+}
+
+const MOORE: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+const VON_NEUMANN: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+impl Sensor {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ahead" => Some(Sensor::Ahead),
+            "moore" => Some(Sensor::Moore),
+            "neumann" | "vonneumann" => Some(Sensor::VonNeumann),
+            _ => None,
+        }
+    }
+
+    // Number of distinct readings for a rule with `cell_states` cell states
+    pub fn range(&self, cell_states: usize) -> usize {
+        match self {
+            Sensor::Ahead => cell_states,
+            Sensor::Moore => MOORE.len() + 1,
+            Sensor::VonNeumann => VON_NEUMANN.len() + 1,
+        }
+    }
+
+    pub fn read(&self, grid: &Grid, x: i32, y: i32, direction: Direction, width: i32, height: i32) -> u8 {
+        let wrap = |(nx, ny): (i32, i32)| (((nx % width) + width) % width, ((ny % height) + height) % height);
+        let count = |offsets: &[(i32, i32)]| {
+            offsets.iter()
+                .map(|&(dx, dy)| wrap((x + dx, y + dy)))
+                .filter(|&(nx, ny)| grid.get_cell(nx, ny) != Grid::EMPTY)
+                .count() as u8
+        };
+        match self {
+            Sensor::Ahead => {
+                let (ax, ay) = wrap(direction.apply(x, y));
+                state_index(grid.get_cell(ax, ay)).unwrap_or(0) as u8
+            }
+            Sensor::Moore => count(&MOORE),
+            Sensor::VonNeumann => count(&VON_NEUMANN),
+        }
+    }
+}
+
+// Transitions keyed by (state, cell, reading), anything missing falls back to the base rule
+#[derive(Debug, Clone)]
+pub struct SensorTable {
+    pub sensor: Sensor,
+    pub entries: BTreeMap<(usize, char, u8), StateTransition>,
+}
+
+impl SensorTable {
+    #[inline]
+    pub fn get(&self, state: usize, cell: char, reading: u8) -> Option<&StateTransition> {
+        self.entries.get(&(state, cell, reading))
+    }
+}
+
+// Splits "RL@moore;0.0.3=L" into the base rule and the sensor section
+pub fn split_sensor_section(rule: &str) -> (&str, Option<&str>) {
+    match rule.split_once('@') {
+        Some((base, section)) => (base, Some(section)),
+        None => (rule, None),
+    }
+}
+
+// Parses "moore;0.0.3=L;1.1.0=R1>0", entries are state.cell.reading=transition
+pub fn parse_sensor_table(section: &str) -> Result<SensorTable, String> {
+    let mut parts = section.split(';').map(str::trim);
+    let name = parts.next().unwrap_or_default();
+    let sensor = Sensor::from_name(name)
+        .ok_or_else(|| format!("unknown sensor '{}'. Must be ahead, moore or neumann", name))?;
+
+    let mut entries = BTreeMap::new();
+    for entry in parts.filter(|p| !p.is_empty()) {
+        let (key, transition) = entry.split_once('=')
+            .ok_or_else(|| format!("sensor entry '{}' must look like state.cell.reading=transition", entry))?;
+        let key: Vec<&str> = key.split('.').map(str::trim).collect();
+        let [state, cell, reading] = key[..] else {
+            return Err(format!("sensor entry '{}' must have a state.cell.reading key", entry));
+        };
+        let state = state.parse::<usize>()
+            .map_err(|_| format!("invalid state '{}' in sensor entry '{}'", state, entry))?;
+        let cell = cell.parse::<usize>().ok().filter(|&c| c < 52)
+            .ok_or_else(|| format!("invalid cell '{}' in sensor entry '{}'", cell, entry))?;
+        let reading = reading.parse::<u8>()
+            .map_err(|_| format!("invalid reading '{}' in sensor entry '{}'", reading, entry))?;
+        // Ahead reads a cell state, so it's bounded by the 52 states a cell can hold
+        if reading as usize >= sensor.range(52) {
+            return Err(format!("reading {} is out of range for sensor '{}' in '{}'", reading, name, entry));
+        }
+        let parsed = rules::parse_transition(transition.trim(), state, cell)?
+            .ok_or_else(|| format!("invalid transition '{}' in sensor entry '{}'", transition, entry))?;
+        entries.insert((state, state_char(cell), reading), parsed);
+    }
+
+    Ok(SensorTable { sensor, entries })
+}
+
+// Size of the key space a sensor table spans on top of its base rule
+pub fn key_space(base: &BTreeMap<(usize, char), StateTransition>, table: &SensorTable) -> usize {
+    let transitions = base.values().chain(table.entries.values());
+
+    let states = base.keys().map(|&(state, _)| state + 1)
+        .chain(table.entries.keys().map(|&(state, _, _)| state + 1))
+        .chain(transitions.clone().map(|t| t.new_internal_state + 1))
+        .max()
+        .unwrap_or(1);
+    // Only written states are reachable, string rules list many more keys
+    let cells = transitions.filter_map(|t| state_index(t.new_cell_state))
+        .chain(table.entries.keys().filter_map(|&(_, cell, _)| state_index(cell)))
+        .max()
+        .map_or(1, |c| c + 1);
+    states * cells * table.sensor.range(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+    use crate::machine::TurnDirection;
+
+    #[test]
+    fn sensor_section_parses_entries() {
+        let (base, section) = split_sensor_section("RL@moore;0.0.3=L;0.1.0=U1>0");
+        assert_eq!(base, "RL");
+        let table = parse_sensor_table(section.unwrap()).unwrap();
+        assert_eq!(table.sensor, Sensor::Moore);
+        assert_eq!(table.get(0, 'A', 3).unwrap().turn_direction, TurnDirection::Left);
+        let u_turn = table.get(0, 'B', 0).unwrap();
+        assert_eq!(u_turn.turn_direction, TurnDirection::UTurn);
+        assert_eq!(u_turn.new_cell_state, 'B');
+        assert!(table.get(0, 'A', 4).is_none());
+    }
+
+    #[test]
+    fn sensor_section_rejects_bad_entries() {
+        assert!(parse_sensor_table("smell;0.0.0=L").is_err());
+        assert!(parse_sensor_table("moore;0.0.9=L").is_err());
+        assert!(parse_sensor_table("neumann;0.0.5=L").is_err());
+        assert!(parse_sensor_table("ahead;0.0.52=L").is_err());
+        assert!(parse_sensor_table("ahead;0.0.51=L").is_ok());
+        assert!(parse_sensor_table("moore;0.0=L").is_err());
+        assert!(parse_sensor_table("moore;0.0.0").is_err());
+        assert!(parse_sensor_table("ahead;0.0.7=R").is_ok());
+    }
+
+    #[test]
+    fn readings_wrap_around_the_torus() {
+        let mut grid = Grid::new();
+        grid.set_cell(4, 0, 'B', Color::White, None, false);
+        grid.set_cell(0, 4, 'C', Color::White, None, false);
+        grid.set_cell(1, 1, 'B', Color::White, None, false);
+        assert_eq!(Sensor::Moore.read(&grid, 0, 0, Direction::Up, 5, 5), 3);
+        assert_eq!(Sensor::VonNeumann.read(&grid, 0, 0, Direction::Up, 5, 5), 2);
+        assert_eq!(Sensor::Ahead.read(&grid, 0, 0, Direction::Up, 5, 5), 2);
+        assert_eq!(Sensor::Ahead.read(&grid, 0, 0, Direction::Right, 5, 5), 0);
+    }
+
+    #[test]
+    fn key_space_grows_with_states_and_sensor_range() {
+        let base = rules::parse_rules("RL").unwrap();
+        let moore = parse_sensor_table("moore").unwrap();
+        assert_eq!(key_space(&base, &moore), 2 * 9);
+        let wide = rules::parse_rules("RLRLRLRLRLRLRLRLRLRLRLRLRLRLRLRL:RL:RL").unwrap();
+        let ahead = parse_sensor_table("ahead").unwrap();
+        assert_eq!(key_space(&wide, &ahead), 3 * 32 * 32);
+    }
+}
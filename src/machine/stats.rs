@@ -0,0 +1,253 @@
+use std::time::Instant;
+use rustc_hash::FxHashSet;
+use super::grid::Grid;
+use super::rules::{self, TurnDirection};
+
+// Population samples kept for the sparkline, older ones are thinned out to make room
+const HISTORY_LEN: usize = 240;
+// Steps between throughput measurements
+const THROUGHPUT_INTERVAL: u64 = 256;
+// Cell states a rule can name, 'A'-'Z' then 'a'-'z'
+const STATES: usize = 52;
+
+pub const TURN_NAMES: [&str; 5] = ["none", "right", "u_turn", "left", "absolute"];
+
+#[inline]
+fn turn_index(turn: TurnDirection) -> usize {
+    match turn {
+        TurnDirection::None => 0,
+        TurnDirection::Right => 1,
+        TurnDirection::UTurn => 2,
+        TurnDirection::Left => 3,
+        TurnDirection::Absolute(_) => 4,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeadStats {
+    pub distance: u64,
+    // Unwrapped, so a head that circles the torus shows how far it really went
+    pub displacement: (i64, i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct RunStats {
+    histogram: [u64; STATES],
+    // Visited cells cost a set insert per step, so they're only kept from the step
+    // something shows or exports them
+    visits_since: Option<u64>,
+    run_start: u64,
+    visited: FxHashSet<(i32, i32)>,
+    // min x, min y, max x, max y over visited cells
+    pub bounds: Option<(i32, i32, i32, i32)>,
+    pub heads: Vec<HeadStats>,
+    pub turns: [u64; 5],
+    pub history: Vec<u64>,
+    history_interval: u64,
+    throughput: f64,
+    last_measure: (Instant, u64),
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        Self {
+            histogram: [0; STATES],
+            visits_since: None,
+            run_start: 0,
+            visited: FxHashSet::default(),
+            bounds: None,
+            heads: Vec::new(),
+            turns: [0; 5],
+            history: Vec::with_capacity(HISTORY_LEN),
+            history_interval: 16,
+            throughput: 0.0,
+            last_measure: (Instant::now(), 0),
+        }
+    }
+
+    // Visit tracking carries over into the new run
+    pub fn reset(&mut self, grid: &Grid, heads: usize, steps: u64) {
+        let tracking = self.visits_since.is_some();
+        *self = Self::new();
+        for &state in grid.tape.values().filter(|&&c| c != Grid::EMPTY) {
+            self.cell_delta(Grid::EMPTY, state);
+        }
+        self.heads = vec![HeadStats::default(); heads];
+        self.last_measure = (Instant::now(), steps);
+        self.run_start = steps;
+        self.visits_since = tracking.then_some(steps);
+    }
+
+    #[inline]
+    pub fn cell_delta(&mut self, old: char, new: char) {
+        if old == new {
+            return;
+        }
+        if old != Grid::EMPTY
+            && let Some(count) = rules::state_index(old).and_then(|i| self.histogram.get_mut(i))
+        {
+            *count = count.saturating_sub(1);
+        }
+        if new != Grid::EMPTY
+            && let Some(count) = rules::state_index(new).and_then(|i| self.histogram.get_mut(i))
+        {
+            *count += 1;
+        }
+    }
+
+    // States with cells on the tape and how many
+    pub fn histogram(&self) -> impl Iterator<Item = (char, u64)> + '_ {
+        self.histogram.iter().enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(i, &count)| (rules::state_char(i), count))
+    }
+
+    // Starts or stops keeping visited cells, which count from the step tracking starts
+    pub fn track_visits(&mut self, enabled: bool, steps: u64) {
+        match (enabled, self.visits_since) {
+            (true, None) => self.visits_since = Some(steps),
+            (false, Some(_)) => {
+                self.visits_since = None;
+                self.visited = FxHashSet::default();
+                self.bounds = None;
+            }
+            _ => {}
+        }
+    }
+
+    // Step visits have been counted from, if later than the run's start
+    pub fn visits_since(&self) -> Option<u64> {
+        self.visits_since.filter(|&since| since > self.run_start)
+    }
+
+    #[inline]
+    pub fn visit(&mut self, x: i32, y: i32, turn: TurnDirection) {
+        self.turns[turn_index(turn)] += 1;
+        if self.visits_since.is_some() && self.visited.insert((x, y)) {
+            let (min_x, min_y, max_x, max_y) = self.bounds.unwrap_or((x, y, x, y));
+            self.bounds = Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)));
+        }
+    }
+
+    #[inline]
+    pub fn head_moved(&mut self, index: usize, dx: i32, dy: i32) {
+        if let Some(head) = self.heads.get_mut(index) {
+            head.distance += (dx.unsigned_abs() + dy.unsigned_abs()) as u64;
+            head.displacement.0 += dx as i64;
+            head.displacement.1 += dy as i64;
+        }
+    }
+
+    pub fn on_step_end(&mut self, steps: u64) {
+        if steps.is_multiple_of(self.history_interval) {
+            if self.history.len() == HISTORY_LEN {
+                // Keep every other sample and sample half as often from here on
+                let thinned: Vec<u64> = self.history.iter().step_by(2).copied().collect();
+                self.history = thinned;
+                self.history_interval *= 2;
+            }
+            self.history.push(self.population());
+        }
+        if steps.is_multiple_of(THROUGHPUT_INTERVAL) {
+            let (since, at_step) = self.last_measure;
+            let elapsed = since.elapsed().as_secs_f64();
+            if elapsed > 0.0 {
+                self.throughput = steps.saturating_sub(at_step) as f64 / elapsed;
+            }
+            self.last_measure = (Instant::now(), steps);
+        }
+    }
+
+    pub fn population(&self) -> u64 {
+        self.histogram.iter().sum()
+    }
+
+    pub fn visited(&self) -> usize {
+        self.visited.len()
+    }
+
+    // Steps per second over the last measurement window
+    pub fn throughput(&self) -> f64 {
+        self.throughput
+    }
+
+    // Columns for the first `states` cell states and every head
+    pub fn csv_header(&self, states: usize) -> String {
+        let mut columns: Vec<String> = ["step", "population", "visited", "bounds_width", "bounds_height"]
+            .iter().map(|name| name.to_string()).collect();
+        columns.extend(TURN_NAMES.iter().map(|name| format!("turns_{}", name)));
+        columns.extend((0..states.min(STATES)).map(|i| format!("cells_{}", rules::state_char(i))));
+        for head in 1..=self.heads.len() {
+            columns.extend(["distance", "dx", "dy"].iter().map(|name| format!("head{}_{}", head, name)));
+        }
+        columns.push("steps_per_second".to_string());
+        columns.join(",")
+    }
+
+    pub fn csv_row(&self, steps: u64, states: usize) -> String {
+        let (width, height) = self.bounds
+            .map_or((0, 0), |(min_x, min_y, max_x, max_y)| (max_x - min_x + 1, max_y - min_y + 1));
+        let mut columns: Vec<String> = [steps, self.population(), self.visited() as u64, width as u64, height as u64]
+            .iter().map(u64::to_string).collect();
+        columns.extend(self.turns.iter().map(u64::to_string));
+        columns.extend(self.histogram[..states.min(STATES)].iter().map(u64::to_string));
+        for head in &self.heads {
+            columns.extend([head.distance as i64, head.displacement.0, head.displacement.1].iter().map(i64::to_string));
+        }
+        columns.push(format!("{:.0}", self.throughput));
+        columns.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_and_visits_follow_the_run() {
+        let mut stats = RunStats::new();
+        stats.reset(&Grid::new(), 1, 0);
+        stats.cell_delta(Grid::EMPTY, 'B');
+        stats.cell_delta(Grid::EMPTY, 'B');
+        stats.cell_delta('B', 'C');
+        stats.cell_delta(Grid::EMPTY, 'b');
+        stats.cell_delta('b', Grid::EMPTY);
+        assert_eq!(stats.histogram().collect::<Vec<_>>(), vec![('B', 1), ('C', 1)]);
+        assert_eq!(stats.population(), 2);
+
+        // Visits are only kept once something asks for them
+        stats.visit(7, 7, TurnDirection::None);
+        assert_eq!(stats.visited(), 0);
+        stats.track_visits(true, 0);
+        assert_eq!(stats.visits_since(), None);
+        stats.visit(2, 3, TurnDirection::Left);
+        stats.visit(-1, 5, TurnDirection::Left);
+        stats.visit(2, 3, TurnDirection::Right);
+        assert_eq!(stats.visited(), 2);
+        assert_eq!(stats.bounds, Some((-1, 3, 2, 5)));
+        assert_eq!(stats.turns, [1, 1, 0, 2, 0]);
+
+        stats.head_moved(0, 2, -1);
+        stats.head_moved(0, -1, 0);
+        assert_eq!(stats.heads[0], HeadStats { distance: 4, displacement: (1, -1) });
+        assert_eq!(
+            stats.csv_header(3),
+            "step,population,visited,bounds_width,bounds_height,turns_none,turns_right,turns_u_turn,turns_left,turns_absolute,\
+             cells_A,cells_B,cells_C,head1_distance,head1_dx,head1_dy,steps_per_second"
+        );
+        assert_eq!(stats.csv_row(7, 3), "7,2,2,4,3,1,1,0,2,0,0,1,1,4,1,-1,0");
+
+        stats.track_visits(false, 7);
+        assert_eq!((stats.visited(), stats.bounds), (0, None));
+    }
+
+    #[test]
+    fn history_thins_out_instead_of_growing() {
+        let mut stats = RunStats::new();
+        for step in 1..=100_000 {
+            stats.on_step_end(step);
+        }
+        assert!(stats.history.len() <= HISTORY_LEN);
+        assert!(stats.history.len() > HISTORY_LEN / 2);
+    }
+}
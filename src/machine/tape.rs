@@ -0,0 +1,233 @@
+use ratatui::style::Color;
+use rand::{Rng, rngs::StdRng};
+use super::{Grid, rules};
+use crate::{config::Config, render::color};
+
+// States a tape can hold, one letter per state
+const MAX_STATES: usize = 52;
+
+// Image rows, None for transparent pixels
+type Pixels = Vec<Vec<Option<(u8, u8, u8)>>>;
+
+// What a run starts on, laid down on every reset
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum InitialTape {
+    #[default]
+    Blank,
+    Random(f64),                // each cell written with this probability
+    Checkerboard(u32),          // squares this many cells wide
+    Border,                     // a wall around the edge of the grid
+    Pattern(Vec<Vec<char>>),    // rows read from a file, centred on the grid
+    Image(Pixels),              // PNG pixels, transparent ones blank, centred like patterns
+}
+
+impl InitialTape {
+    // "", "random:P", "checkerboard[:SIZE]", "border", or a text, .rle or .png file relative to the config dir
+    pub fn load(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        match spec.split_once(':') {
+            Some(("random", density)) => match density.trim().parse::<f64>() {
+                Ok(p) if (0.0..=1.0).contains(&p) => Ok(InitialTape::Random(p)),
+                _ => Err(format!("random density must be between 0 and 1, found '{}'", density)),
+            },
+            Some(("checkerboard", size)) => match size.trim().parse::<u32>() {
+                Ok(size) if size > 0 => Ok(InitialTape::Checkerboard(size)),
+                _ => Err(format!("checkerboard size must be a positive number, found '{}'", size)),
+            },
+            _ => match spec {
+                "" => Ok(InitialTape::Blank),
+                "random" => Ok(InitialTape::Random(0.5)),
+                "checkerboard" => Ok(InitialTape::Checkerboard(1)),
+                "border" => Ok(InitialTape::Border),
+                path => Self::load_file(path),
+            },
+        }
+    }
+
+    fn load_file(path: &str) -> Result<Self, String> {
+        let resolved = Config::resolve_path(path);
+        let extension = resolved.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        if extension.as_deref() == Some("png") {
+            let bytes = std::fs::read(&resolved).map_err(|e| format!("can't read '{}': {}", resolved.display(), e))?;
+            return parse_png(&bytes).map(InitialTape::Image).map_err(|e| format!("{}: {}", resolved.display(), e));
+        }
+        let text = std::fs::read_to_string(&resolved)
+            .map_err(|e| format!("can't read '{}': {}", resolved.display(), e))?;
+        let rows = match extension.as_deref() {
+            Some("rle") => parse_rle(&text),
+            _ => parse_text(&text),
+        };
+        rows.map(InitialTape::Pattern).map_err(|e| format!("{}: {}", resolved.display(), e))
+    }
+
+    // Cells to write on a width x height grid, random fills use the rule's written states.
+    // Images take the state whose colour is nearest each pixel, `colors` holds one per state
+    pub fn cells(&self, width: i32, height: i32, states: usize, colors: &[Color], rng: &mut StdRng) -> Vec<(i32, i32, char)> {
+        let positions = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        match self {
+            InitialTape::Blank => Vec::new(),
+            InitialTape::Random(p) => {
+                let written = states.clamp(2, MAX_STATES);
+                positions
+                    .filter_map(|(x, y)| match rng.random_bool(*p) {
+                        true => Some((x, y, rules::state_char(rng.random_range(1..written)))),
+                        false => None,
+                    })
+                    .collect()
+            }
+            InitialTape::Checkerboard(size) => {
+                let size = *size as i32;
+                positions.filter(|(x, y)| (x / size + y / size) % 2 == 1).map(|(x, y)| (x, y, 'B')).collect()
+            }
+            InitialTape::Border => positions
+                .filter(|&(x, y)| x == 0 || y == 0 || x == width - 1 || y == height - 1)
+                .map(|(x, y)| (x, y, 'B'))
+                .collect(),
+            InitialTape::Pattern(rows) => centred(rows, width, height, |&state| state),
+            InitialTape::Image(rows) => centred(rows, width, height, |pixel| {
+                pixel.and_then(|rgb| color::nearest(rgb, colors)).map_or(Grid::EMPTY, rules::state_char)
+            }),
+        }
+    }
+}
+
+// Rows centred on the grid and clipped to it, blank cells left out
+fn centred<T>(rows: &[Vec<T>], width: i32, height: i32, state: impl Fn(&T) -> char) -> Vec<(i32, i32, char)> {
+    let pattern_width = rows.iter().map(Vec::len).max().unwrap_or(0) as i32;
+    let (left, top) = ((width - pattern_width) / 2, (height - rows.len() as i32) / 2);
+    rows.iter().enumerate()
+        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, cell)| (left + x as i32, top + y as i32, cell)))
+        .map(|(x, y, cell)| (x, y, state(cell)))
+        .filter(|&(x, y, state)| state != Grid::EMPTY && (0..width).contains(&x) && (0..height).contains(&y))
+        .collect()
+}
+
+// One letter per cell, '.' or space for blank. Lines starting with '#' are skipped,
+// so saved snapshots load as they are
+pub fn parse_text(text: &str) -> Result<Vec<Vec<char>>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.starts_with('#'))
+        .map(|(number, line)| {
+            line.trim_end().chars().enumerate().map(|(column, c)| match c {
+                '.' | ' ' => Ok(Grid::EMPTY),
+                c if rules::state_index(c).is_some() => Ok(c),
+                _ => Err(format!("line {}, column {}: '{}' is not a state, use A-Z, a-z or '.'", number + 1, column + 1, c)),
+            }).collect()
+        })
+        .collect()
+}
+
+// Pixels by row, those less than half opaque are None. Any bit depth or colour type is read as 8-bit colour
+pub fn parse_png(bytes: &[u8]) -> Result<Pixels, String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let channels = info.color_type.samples();
+    let pixel = |p: &[u8]| {
+        let (rgb, alpha) = match p {
+            [grey] => ((*grey, *grey, *grey), 255),
+            [grey, alpha] => ((*grey, *grey, *grey), *alpha),
+            [r, g, b] => ((*r, *g, *b), 255),
+            [r, g, b, alpha, ..] => ((*r, *g, *b), *alpha),
+            [] => ((0, 0, 0), 0),
+        };
+        (alpha >= 128).then_some(rgb)
+    };
+    Ok(buffer[..info.buffer_size()]
+        .chunks(info.line_size)
+        .map(|line| line[..info.width as usize * channels].chunks(channels).map(pixel).collect())
+        .collect())
+}
+
+// Golly's run length encoding: 'b'/'.' blank, 'o' state 1, 'A'-'X' states 1-24, '$' ends a row, '!' ends the pattern
+pub fn parse_rle(text: &str) -> Result<Vec<Vec<char>>, String> {
+    let mut rows = vec![Vec::new()];
+    let mut run = 0usize;
+    let body = text.lines().map(str::trim).filter(|line| !line.starts_with('#') && !line.starts_with('x'));
+    for c in body.flat_map(str::chars) {
+        let count = run.max(1);
+        match c {
+            '0'..='9' => {
+                run = run * 10 + c.to_digit(10).unwrap_or(0) as usize;
+                continue;
+            }
+            'b' | '.' => rows.last_mut().into_iter().for_each(|row| row.extend(std::iter::repeat_n(Grid::EMPTY, count))),
+            'o' => rows.last_mut().into_iter().for_each(|row| row.extend(std::iter::repeat_n('B', count))),
+            'A'..='X' => {
+                let state = (c as u8 + 1) as char;
+                rows.last_mut().into_iter().for_each(|row| row.extend(std::iter::repeat_n(state, count)));
+            }
+            '$' => rows.extend(std::iter::repeat_n(Vec::new(), count)),
+            '!' => return Ok(rows),
+            c if c.is_whitespace() => {}
+            'p'..='y' => return Err("states past 24 aren't supported".to_string()),
+            _ => return Err(format!("unexpected '{}'", c)),
+        }
+        run = 0;
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn text_and_rle_describe_the_same_pattern() {
+        let text = parse_text("# rule: RL\nB.C\n.B\n").unwrap();
+        assert_eq!(text, vec![vec!['B', 'A', 'C'], vec!['A', 'B']]);
+        assert_eq!(parse_rle("x = 3, y = 2, rule = Turmite\nAbB$bA!").unwrap(), text);
+        assert_eq!(parse_rle("2o$3b!").unwrap(), vec![vec!['B', 'B'], vec!['A', 'A', 'A']]);
+        assert!(parse_text("B#").is_err());
+        // Snapshots of runs past 26 states hold lowercase states
+        assert_eq!(parse_text("a.z").unwrap(), vec![vec!['a', 'A', 'z']]);
+        assert!(parse_rle("pA!").is_err());
+    }
+
+    #[test]
+    fn procedural_tapes_follow_the_seed() {
+        assert_eq!(InitialTape::load("random:0.25"), Ok(InitialTape::Random(0.25)));
+        assert_eq!(InitialTape::load("checkerboard:3"), Ok(InitialTape::Checkerboard(3)));
+        assert!(InitialTape::load("random:2").is_err());
+        assert!(InitialTape::load("missing.png").is_err());
+
+        let fill = |seed| InitialTape::Random(0.5).cells(20, 10, 3, &[], &mut StdRng::seed_from_u64(seed));
+        assert_eq!(fill(1), fill(1));
+        assert_ne!(fill(1), fill(2));
+        assert!(fill(1).iter().all(|&(_, _, state)| state == 'B' || state == 'C'));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(InitialTape::Border.cells(4, 3, 2, &[], &mut rng).len(), 10);
+        assert_eq!(InitialTape::Checkerboard(1).cells(4, 4, 2, &[], &mut rng).len(), 8);
+        // Patterns are centred and clipped to the grid
+        let pattern = InitialTape::Pattern(vec![vec!['B', 'A', 'C']]);
+        assert_eq!(pattern.cells(5, 3, 3, &[], &mut rng), vec![(1, 1, 'B'), (3, 1, 'C')]);
+        assert_eq!(pattern.cells(1, 1, 3, &[], &mut rng), vec![]);
+    }
+
+    #[test]
+    fn png_pixels_take_the_nearest_state_colour() {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 3, 2);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[
+            250, 10, 10, 255,   0, 0, 0, 0,         20, 20, 230, 255,
+            0, 0, 0, 255,       240, 30, 0, 200,    255, 0, 0, 100,
+        ]).unwrap();
+        writer.finish().unwrap();
+
+        let pixels = parse_png(&bytes).unwrap();
+        assert_eq!(pixels[0], vec![Some((250, 10, 10)), None, Some((20, 20, 230))]);
+        let colors = [Color::Rgb(0, 0, 0), Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)];
+        let cells = InitialTape::Image(pixels).cells(3, 2, 3, &colors, &mut StdRng::seed_from_u64(0));
+        // Black is the blank state, half transparent pixels are left out
+        assert_eq!(cells, vec![(0, 0, 'B'), (2, 0, 'C'), (1, 1, 'B')]);
+        assert!(parse_png(b"not a png").is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use super::detection::offset;
+
+// Acted steps without a rule before a head counts as stalled
+const STALL_STEPS: u32 = 16;
+// Moves per orbit check, and the widest box a head may keep to in that time to count as orbiting
+const ORBIT_WINDOW: u32 = 256;
+const ORBIT_SPAN: i32 = 4;
+// Moves in a row that leave the tape as it was before a head counts as idle
+const IDLE_STEPS: u32 = 512;
+
+// What a single head is up to, independent of whether the run as a whole is stuck
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HeadActivity {
+    #[default]
+    Active,
+    Stalled,        // no rule for what it reads
+    Orbiting,       // keeps to a small patch of the tape
+    Idle,           // moves but never changes a cell
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HeadWatch {
+    no_rule: u32,
+    unchanged: u32,
+    moves: u32,
+    // min x, min y, max x, max y over the current orbit window, unwrapped across the torus edges
+    bounds: Option<(i32, i32, i32, i32)>,
+    // Last position on the torus, and where it lies unwrapped
+    last: Option<((i32, i32), (i32, i32))>,
+    orbiting: bool,
+}
+
+impl HeadWatch {
+    pub fn on_no_rule(&mut self) {
+        self.no_rule = self.no_rule.saturating_add(1);
+    }
+
+    // Positions are on a torus of `size`, so a patch across an edge stays small
+    pub fn on_act(&mut self, x: i32, y: i32, modified: bool, size: (i32, i32)) {
+        self.no_rule = 0;
+        self.unchanged = if modified { 0 } else { self.unchanged.saturating_add(1) };
+
+        let unwrapped = match self.last {
+            Some(((last_x, last_y), (unwrapped_x, unwrapped_y))) => {
+                (unwrapped_x + offset(last_x, x, size.0), unwrapped_y + offset(last_y, y, size.1))
+            }
+            None => (x, y),
+        };
+        self.last = Some(((x, y), unwrapped));
+        let (x, y) = unwrapped;
+        let (min_x, min_y, max_x, max_y) = self.bounds.unwrap_or((x, y, x, y));
+        self.bounds = Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)));
+        self.moves += 1;
+        if self.moves == ORBIT_WINDOW {
+            if let Some((min_x, min_y, max_x, max_y)) = self.bounds {
+                self.orbiting = max_x - min_x <= ORBIT_SPAN && max_y - min_y <= ORBIT_SPAN;
+            }
+            self.moves = 0;
+            self.bounds = None;
+            self.last = None;
+        }
+    }
+
+    pub fn activity(&self) -> HeadActivity {
+        if self.no_rule >= STALL_STEPS {
+            HeadActivity::Stalled
+        } else if self.orbiting {
+            HeadActivity::Orbiting
+        } else if self.unchanged >= IDLE_STEPS {
+            HeadActivity::Idle
+        } else {
+            HeadActivity::Active
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_stalled_orbiting_and_idle_heads() {
+        let mut stalled = HeadWatch::default();
+        for _ in 0..STALL_STEPS {
+            assert_eq!(stalled.activity(), HeadActivity::Active);
+            stalled.on_no_rule();
+        }
+        assert_eq!(stalled.activity(), HeadActivity::Stalled);
+        stalled.on_act(0, 0, true, (100, 100));
+        assert_eq!(stalled.activity(), HeadActivity::Active);
+
+        let mut orbiting = HeadWatch::default();
+        for i in 0..ORBIT_WINDOW as i32 {
+            orbiting.on_act(10 + i % 2, 10 + i % 3, true, (100, 100));
+        }
+        assert_eq!(orbiting.activity(), HeadActivity::Orbiting);
+
+        let mut idle = HeadWatch::default();
+        for i in 0..IDLE_STEPS as i32 {
+            idle.on_act(i, 0, false, (1000, 1000));
+        }
+        assert_eq!(idle.activity(), HeadActivity::Idle);
+    }
+
+    #[test]
+    fn wandering_head_is_not_orbiting() {
+        let mut watch = HeadWatch::default();
+        for i in 0..ORBIT_WINDOW as i32 * 2 {
+            watch.on_act(i / 8, i % 8, true, (100, 100));
+        }
+        assert_eq!(watch.activity(), HeadActivity::Active);
+    }
+
+    #[test]
+    fn orbits_across_the_torus_edge_count() {
+        // Circles the corner cell of a 20x10 torus, touching all four edges
+        let corner = [(19, 9), (0, 9), (0, 0), (19, 0)];
+        let mut orbiting = HeadWatch::default();
+        for i in 0..ORBIT_WINDOW as usize {
+            let (x, y) = corner[i % 4];
+            orbiting.on_act(x, y, true, (20, 10));
+        }
+        assert_eq!(orbiting.activity(), HeadActivity::Orbiting);
+
+        // A head marching around the torus is still on the move
+        let mut marching = HeadWatch::default();
+        for i in 0..ORBIT_WINDOW as i32 {
+            marching.on_act(i % 20, 3, true, (20, 10));
+        }
+        assert_eq!(marching.activity(), HeadActivity::Active);
+    }
+}
@@ -1,6 +1,7 @@
 mod config;
 mod machine;
 mod render;
+mod headless;
 
 use ratatui::{
     backend::CrosstermBackend,
@@ -14,14 +15,24 @@ use crossterm::{
 use std::{
     error::Error,
     io,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
 use config::{Config, ConfigLoadResult};
 use render::{App, ui};
+use headless::HeadlessOptions;
+
+struct CliArgs {
+    rule: Option<String>,
+    seed: Option<String>,
+    headless: Option<HeadlessOptions>,
+    export_golly: Option<PathBuf>,
+    preset: Option<String>,
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let (cli_rule, cli_seed) = parse_cli_args();
+    let cli = parse_cli_args();
 
     let (config, error_message) = match Config::load() {
         ConfigLoadResult::Success(config) => (config, None),
@@ -36,15 +47,60 @@ fn main() -> Result<(), Box<dyn Error>> {
         },
     };
     
-    Config::apply_cli_overrides(cli_rule.as_deref(), cli_seed.as_deref());
+    // Rule and seed flags still win over the preset's. Only this launch runs it, the state
+    // files keep what was there, unlike loading it from the browser
+    let (config, preset_run) = match &cli.preset {
+        Some(name) => match config::presets::find(name).and_then(|preset| preset.apply(&config)) {
+            Ok(config) => {
+                let seed = cli.seed.clone().or_else(|| config.simulation.seed.clone());
+                let rule = cli.rule.clone().unwrap_or_else(|| config.simulation.rule.clone());
+                (config, Some((seed, rule)))
+            }
+            Err(e) => {
+                eprintln!("error: can't load preset: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => (config, None),
+    };
+    let preset_run = preset_run.as_ref().map(|(seed, rule)| (seed.as_deref(), rule.as_str()));
+    Config::apply_cli_overrides(cli.rule.as_deref(), cli.seed.as_deref());
+
+    if let Some(dir) = cli.export_golly {
+        let rule = preset_run.map_or_else(|| config.get_effective_rule(), |(_, rule)| rule.to_string());
+        match export_golly(&rule, &dir) {
+            Ok(path) => println!("{}", path.display()),
+            Err(e) => {
+                eprintln!("error: can't export to Golly: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(options) = cli.headless {
+        if let Some(error) = error_message {
+            eprintln!("warning: {}", error);
+        }
+        return headless::run(&config, &options, preset_run);
+    }
 
     enable_raw_mode()?;
+    let mut config = config;
+    let terminal_background = match config.display.wants_background() {
+        true => render::background::query_background(Duration::from_secs(1)),
+        false => None,
+    };
+    render::resolve_background(&mut config, terminal_background);
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::new(config);
+    if let Some((seed, rule)) = preset_run {
+        app.run_unsaved(seed, rule);
+    }
     
     // Show error if config loading failed
     if let Some(error) = error_message {
@@ -68,9 +124,25 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn parse_cli_args() -> (Option<String>, Option<String>) {
+// Writes the rule as a Golly rule table named after the rule
+fn export_golly(rule: &str, dir: &Path) -> Result<PathBuf, String> {
+    let (base, sensors) = machine::sensors::split_sensor_section(rule);
+    if sensors.is_some() {
+        return Err("rules with sensors have no Golly equivalent".to_string());
+    }
+    let rules = machine::rules::parse_rules(base)?;
+    let table = machine::golly::export_rule_table(&rules)?;
+    let path = dir.join(format!("{}.rule", machine::golly::export_name(&rules)?));
+    std::fs::write(&path, table).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn parse_cli_args() -> CliArgs {
     let mut rule = None;
     let mut seed = None;
+    let mut headless: Option<HeadlessOptions> = None;
+    let mut export_golly = None;
+    let mut preset = None;
     let mut args = std::env::args().skip(1);
 
     while let Some(arg) = args.next() {
@@ -83,6 +155,14 @@ fn parse_cli_args() -> (Option<String>, Option<String>) {
                 println!("options:");
                 println!("  -r, --rule <RULE>  Override the simulation rule");
                 println!("  -s, --seed <SEED>  Override the simulation seed");
+                println!("  -p, --preset <NAME>  Start from a named preset");
+                println!("      --headless     Run without a terminal UI and print a summary");
+                println!("      --steps <N>    Headless step limit (default 10000)");
+                println!("      --size <WxH>   Headless grid size (default 200x100)");
+                println!("      --csv <FILE>   Headless stats export, one row every --csv-every steps");
+                println!("      --csv-every <N>  Steps between CSV rows (default 100)");
+                println!("      --import-golly <FILE>  Run the turmite a Golly .rule file was generated from");
+                println!("      --export-golly <DIR>   Write the rule as a Golly .rule file and exit");
                 println!("  -h, --help         Show this help message");
                 println!("  -v, --version      Show version");
                 println!();
@@ -105,6 +185,53 @@ fn parse_cli_args() -> (Option<String>, Option<String>) {
                     std::process::exit(1);
                 }));
             }
+            "--preset" | "-p" => {
+                preset = Some(parse_value::<String>(&mut args, "--preset"));
+            }
+            "--import-golly" => {
+                let path: PathBuf = parse_value(&mut args, "--import-golly");
+                let imported = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| machine::golly::import_rule_file(&text));
+                match imported {
+                    Ok(name) => rule = Some(name),
+                    Err(e) => {
+                        eprintln!("error: can't import {}: {}", path.display(), e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--export-golly" => {
+                export_golly = Some(parse_value(&mut args, "--export-golly"));
+            }
+            "--headless" => {
+                headless.get_or_insert_with(HeadlessOptions::default);
+            }
+            "--steps" => {
+                headless.get_or_insert_with(HeadlessOptions::default).steps = parse_value(&mut args, "--steps");
+            }
+            "--csv-every" => {
+                headless.get_or_insert_with(HeadlessOptions::default).csv_every = parse_value(&mut args, "--csv-every");
+            }
+            "--csv" => {
+                let path = args.next().unwrap_or_else(|| {
+                    eprintln!("error: --csv requires a value");
+                    std::process::exit(1);
+                });
+                headless.get_or_insert_with(HeadlessOptions::default).csv = Some(path.into());
+            }
+            "--size" => {
+                let size: String = parse_value(&mut args, "--size");
+                let Some((Ok(width), Ok(height))) = size.split_once('x').map(|(w, h)| (w.parse::<i32>(), h.parse::<i32>()))
+                    .filter(|(w, h)| matches!((w, h), (Ok(w), Ok(h)) if *w > 0 && *h > 0))
+                else {
+                    eprintln!("error: --size must look like 200x100");
+                    std::process::exit(1);
+                };
+                let options = headless.get_or_insert_with(HeadlessOptions::default);
+                options.width = width;
+                options.height = height;
+            }
             other => {
                 eprintln!("error: unknown argument '{other}'");
                 eprintln!("usage: trmt [-r|--rule <RULE>] [-s|--seed <SEED>]");
@@ -114,7 +241,16 @@ fn parse_cli_args() -> (Option<String>, Option<String>) {
         }
     }
 
-    (rule, seed)
+    CliArgs { rule, seed, headless, export_golly, preset }
+}
+
+fn parse_value<T: std::str::FromStr>(args: &mut impl Iterator<Item = String>, flag: &str) -> T {
+    args.next()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| {
+            eprintln!("error: {flag} requires a valid value");
+            std::process::exit(1);
+        })
 }
 
 fn run_app(
@@ -124,16 +260,29 @@ fn run_app(
     loop {
         let area = terminal.draw(|f| ui(f, app))?.area;
         
-        if event::poll(Duration::from_millis(16))?
-            && let Event::Key(key) = event::read()?
+        let event = if event::poll(Duration::from_millis(16))? { Some(event::read()?) } else { None };
+        if let Some(Event::Mouse(mouse)) = event {
+            app.handle_mouse(mouse, area);
+        }
+        if let Some(Event::Key(key)) = event
+                && !app.preset_key(key.code)
+                && !app.history_key(key.code)
                 && let KeyCode::Char(ch) = key.code {
                     let ch_str = ch.to_string();
                     let mut key_pressed = false;
                     
                     match ch_str.as_str() {
-                        s if s == app.config.controls.quit => return Ok(()),
-                        s if s == app.config.controls.toggle => { key_pressed = true; app.machine.toggle_running(); },
-                        s if s == app.config.controls.reset => { key_pressed = true; app.machine.reset(&app.config); },
+                        s if s == app.config.controls.quit => {
+                            app.record_run();
+                            return Ok(());
+                        },
+                        s if s == app.config.controls.toggle => { key_pressed = true; app.toggle_running(); },
+                        s if s == app.config.controls.reset => {
+                            key_pressed = true;
+                            app.record_run();
+                            app.machine.reset(&app.config);
+                            app.rebuild_universes();
+                        },
                         s if s == app.config.controls.faster => {
                             key_pressed = true;
                             if app.step_interval > Duration::from_millis(100) {
@@ -168,21 +317,11 @@ fn run_app(
                             match Config::load() {
                                 ConfigLoadResult::Success(config) => {
                                     // Clear runtime state to prioritize config
+                                    app.machine.release();
                                     let _ = Config::clear_current_seed();
                                     let _ = Config::clear_current_rule();
-                                    
-                                    app.config = config;
-                                    app.config.display.cache_char_data();
-                                    app.machine.set_head_count(app.config.simulation.heads, &app.config);
-                                    app.step_interval = Duration::from_nanos((app.config.simulation.speed_ms * 1_000_000.0) as u64);
-                                    
-                                    let effective_rule = app.config.get_effective_rule();
-                                    app.machine.parse_rules(&effective_rule);
-                                    app.machine.rule_string = effective_rule;
-                                    
-                                    app.machine.update_colors(&app.config);
-                                    app.machine.reset(&app.config);
-                                    app.error_message = None;
+                                    app.apply_config(config);
+                                    app.start_slideshow();
                                 }
                                 ConfigLoadResult::ValidationErrors(config, errors) => {
                                     app.config = config;
@@ -198,51 +337,38 @@ fn run_app(
                                 },
                             }
                         },
-                        s if s == app.config.controls.randomize_seed => {
-                            key_pressed = true;
-                            // Generate random seed and reset
-                            let random_seed = app.machine.generate_random_seed();
-                            if let Err(e) = Config::save_current_seed(&random_seed) {
-                                app.show_error(format!("Failed to save random seed: {}", e));
-                            } else {
-                                app.machine.reset_clean(&app.config);
-                            }
-                        },
-                        s if s == app.config.controls.randomize_rule => {
-                            key_pressed = true;
-                            // Generate random rule and reset
-                            let random_rule = Config::generate_random_rule();
-                            if let Err(e) = Config::save_current_rule(&random_rule) {
-                                app.show_error(format!("Failed to save random rule: {}", e));
-                            } else {
-                                app.machine.reset_clean(&app.config);
-                            }
-                        },
-                        s if s == app.config.controls.randomize => {
-                            key_pressed = true;
-                            // Generate random seed and rule, then reset
-                            let random_seed = app.machine.generate_random_seed();
-                            let random_rule = Config::generate_random_rule();
-                            match (Config::save_current_seed(&random_seed), Config::save_current_rule(&random_rule)) {
-                                (Ok(_), Ok(_)) => app.machine.reset_clean(&app.config),
-                                (Err(e), _) | (_, Err(e)) => app.show_error(format!("Failed to save random parameters: {}", e)),
-                            }
-                        },
+                        s if s == app.config.controls.randomize_seed => { key_pressed = true; app.randomize(true, false); },
+                        s if s == app.config.controls.randomize_rule => { key_pressed = true; app.randomize(false, true); },
+                        s if s == app.config.controls.randomize => { key_pressed = true; app.randomize(true, true); },
                         s if s == app.config.controls.step && !app.machine.running => {
                             key_pressed = true;
-                            app.machine.step(area.width as i32 / 2, area.height as i32, &app.config);
-                            app.machine.mark_trail_dirty();
+                            app.step_once(area);
                         },
-                        "1" => { key_pressed = true; app.machine.set_head_count(1, &app.config); },
-                        "2" => { key_pressed = true; app.machine.set_head_count(2, &app.config); },
-                        "3" => { key_pressed = true; app.machine.set_head_count(4, &app.config); },
-                        "4" => { key_pressed = true; app.machine.set_head_count(8, &app.config); },
-                        "5" => { key_pressed = true; app.machine.set_head_count(16, &app.config); },
-                        "6" => { key_pressed = true; app.machine.set_head_count(32, &app.config); },
-                        "7" => { key_pressed = true; app.machine.set_head_count(64, &app.config); },
-                        "8" => { key_pressed = true; app.machine.set_head_count(128, &app.config); },
-                        "9" => { key_pressed = true; app.machine.set_head_count(256, &app.config); },
+                        "1" => { key_pressed = true; app.machine.set_head_count(1, &app.config); app.rebuild_universes(); },
+                        "2" => { key_pressed = true; app.machine.set_head_count(2, &app.config); app.rebuild_universes(); },
+                        "3" => { key_pressed = true; app.machine.set_head_count(4, &app.config); app.rebuild_universes(); },
+                        "4" => { key_pressed = true; app.machine.set_head_count(8, &app.config); app.rebuild_universes(); },
+                        "5" => { key_pressed = true; app.machine.set_head_count(16, &app.config); app.rebuild_universes(); },
+                        "6" => { key_pressed = true; app.machine.set_head_count(32, &app.config); app.rebuild_universes(); },
+                        "7" => { key_pressed = true; app.machine.set_head_count(64, &app.config); app.rebuild_universes(); },
+                        "8" => { key_pressed = true; app.machine.set_head_count(128, &app.config); app.rebuild_universes(); },
+                        "9" => { key_pressed = true; app.machine.set_head_count(256, &app.config); app.rebuild_universes(); },
+                        s if s == app.config.controls.split => { key_pressed = true; app.cycle_universes(); },
+                        s if s == app.config.controls.diff => { key_pressed = true; app.show_diff = !app.show_diff; },
                         s if s == app.config.controls.help => { key_pressed = true; app.show_help = !app.show_help; },
+                        s if s == app.config.controls.stats => { key_pressed = true; app.show_stats = !app.show_stats; },
+                        s if s == app.config.controls.heatmap => { key_pressed = true; app.show_heatmap = !app.show_heatmap; },
+                        s if s == app.config.controls.presets => { key_pressed = true; app.toggle_presets(); },
+                        s if s == app.config.controls.history => { key_pressed = true; app.toggle_history(); },
+                        s if s == app.config.controls.favourite => { key_pressed = true; app.toggle_favourite(); },
+                        s if s == app.config.controls.history_back => { key_pressed = true; app.walk_history(true); },
+                        s if s == app.config.controls.history_forward => { key_pressed = true; app.walk_history(false); },
+                        s if s == app.config.controls.playlist_next => { key_pressed = true; app.skip_entry(1); },
+                        s if s == app.config.controls.playlist_previous => { key_pressed = true; app.skip_entry(-1); },
+                        s if s == app.config.controls.playlist_pause => { key_pressed = true; app.toggle_slideshow_pause(); },
+                        s if s == app.config.controls.mouse => { key_pressed = true; app.toggle_mouse_mode(); },
+                        s if s == app.config.controls.paint_state => { key_pressed = true; app.cycle_paint_state(); },
+                        s if s == app.config.controls.head_direction => { key_pressed = true; app.rotate_head_direction(1); },
                         s if s == app.config.controls.statusbar => { key_pressed = true; app.show_statusbar = !app.show_statusbar; },
                         "x" => { key_pressed = true; app.clear_overlays(); },
                         _ => {}
@@ -253,6 +379,6 @@ fn run_app(
                     }
                 }
 
-        app.update(area.width as i32 / 2, area.height as i32);
+        app.update(area);
     }
 }
\ No newline at end of file
@@ -0,0 +1,125 @@
+use ratatui::style::Color;
+use std::{
+    io::{self, IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+// Asks for the background (OSC 11), then for device attributes (DA1). Every terminal
+// answers DA1, so the reader knows when to stop even if OSC 11 is not supported
+const QUERY: &[u8] = b"\x1b]11;?\x1b\\\x1b[c";
+
+// Used when the terminal doesn't say, most terminals are dark
+pub const FALLBACK_BACKGROUND: Color = Color::Rgb(0, 0, 0);
+
+// Expects raw mode, otherwise the reply waits for a newline that never comes. Reads on
+// until the DA1 reply, so none of it is left for the event loop to take for keypresses.
+// `timeout` only guards against a terminal that doesn't answer at all
+pub fn query_background(timeout: Duration) -> Option<Color> {
+    if !cfg!(unix) || !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return None;
+    }
+    let mut stdout = io::stdout();
+    stdout.write_all(QUERY).ok()?;
+    stdout.flush().ok()?;
+
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+    while let Some(byte) = read_byte_until(deadline) {
+        reply.push(byte);
+        if ends_with_device_attributes(&reply) {
+            break;
+        }
+    }
+    parse_osc11(&reply)
+}
+
+// One byte straight from the descriptor, so nothing past the reply ends up buffered in std
+#[cfg(unix)]
+fn read_byte_until(deadline: Instant) -> Option<u8> {
+    use rustix::event::{PollFd, PollFlags, Timespec, poll};
+    use std::os::fd::AsFd;
+
+    let stdin = io::stdin();
+    let remaining = Timespec::try_from(deadline.checked_duration_since(Instant::now())?).ok()?;
+    let mut fds = [PollFd::new(&stdin, PollFlags::IN)];
+    if poll(&mut fds, Some(&remaining)).ok()? == 0 || !fds[0].revents().contains(PollFlags::IN) {
+        return None;
+    }
+    let mut byte = [0u8; 1];
+    (rustix::io::read(stdin.as_fd(), &mut byte).ok()? == 1).then_some(byte[0])
+}
+
+#[cfg(not(unix))]
+fn read_byte_until(_deadline: Instant) -> Option<u8> {
+    None
+}
+
+// DA1 replies look like ESC [ ? 6 2 ; 2 2 c
+fn ends_with_device_attributes(reply: &[u8]) -> bool {
+    reply.last() == Some(&b'c')
+        && reply.windows(3).rposition(|w| w == b"\x1b[?")
+            .is_some_and(|start| reply[start + 3..reply.len() - 1].iter().all(|b| b.is_ascii_digit() || *b == b';'))
+}
+
+// Finds ESC ] 11 ; rgb:RRRR/GGGG/BBBB ended by BEL or ESC \, with 1 to 4 hex digits per channel
+pub fn parse_osc11(reply: &[u8]) -> Option<Color> {
+    let start = reply.windows(5).position(|w| w == b"\x1b]11;")? + 5;
+    let body = &reply[start..];
+    let end = body.iter().position(|&b| b == 0x07 || b == 0x1b)?;
+    let body = std::str::from_utf8(&body[..end]).ok()?;
+    let channels = body.strip_prefix("rgb:").or_else(|| body.strip_prefix("rgba:"))?;
+
+    let mut parts = channels.split('/').map(scale_channel);
+    let (r, g, b) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(Color::Rgb(r, g, b))
+}
+
+fn scale_channel(hex: &str) -> Option<u8> {
+    if hex.is_empty() || hex.len() > 4 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let max = (1u32 << (4 * hex.len())) - 1;
+    Some(((value * 255 + max / 2) / max) as u8)
+}
+
+// Perceived brightness past the midpoint
+pub fn is_light(color: Color) -> bool {
+    match super::color::to_rgb(color) {
+        Some((r, g, b)) => 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32 > 128.0,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_canned_replies() {
+        // xterm, four digits per channel and BEL
+        assert_eq!(parse_osc11(b"\x1b]11;rgb:ffff/ffff/dddd\x07"), Some(Color::Rgb(255, 255, 221)));
+        // Terminated with ST and followed by the DA1 reply
+        assert_eq!(parse_osc11(b"\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\\x1b[?62;22c"), Some(Color::Rgb(30, 30, 46)));
+        // Two digits per channel, alpha ignored
+        assert_eq!(parse_osc11(b"\x1b]11;rgba:28/2c/34/ff\x07"), Some(Color::Rgb(40, 44, 52)));
+        // Single digit channels scale up
+        assert_eq!(parse_osc11(b"\x1b]11;rgb:f/0/8\x07"), Some(Color::Rgb(255, 0, 136)));
+    }
+
+    #[test]
+    fn rejects_missing_or_broken_replies() {
+        assert_eq!(parse_osc11(b"\x1b[?1;2c"), None);
+        assert_eq!(parse_osc11(b"\x1b]11;rgb:ffff/ffff\x07"), None);
+        assert_eq!(parse_osc11(b"\x1b]11;rgb:zz/00/00\x07"), None);
+        assert_eq!(parse_osc11(b"\x1b]11;rgb:ffff/ffff/ffff"), None);
+    }
+
+    #[test]
+    fn device_attributes_end_the_reply() {
+        assert!(ends_with_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+        assert!(!ends_with_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;2"));
+        assert!(is_light(Color::Rgb(250, 250, 240)));
+        assert!(!is_light(Color::Rgb(30, 30, 46)));
+    }
+}
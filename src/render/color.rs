@@ -0,0 +1,159 @@
+use ratatui::{buffer::Buffer, style::{Color, Modifier}};
+use crate::config::ColorDepth;
+
+// xterm's defaults for the 16 ANSI colours, in index order
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// Channel levels of the 6x6x6 cube in the 256-colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// Auto resolves from COLORTERM and TERM, anything else is taken as configured
+pub fn effective_depth(setting: ColorDepth) -> ColorDepth {
+    match setting {
+        ColorDepth::Auto => detect_depth(
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::env::var("TERM").ok().as_deref(),
+        ),
+        depth => depth,
+    }
+}
+
+pub fn detect_depth(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+    if matches!(colorterm, Some("truecolor" | "24bit")) {
+        return ColorDepth::Truecolor;
+    }
+    match term.unwrap_or("") {
+        "" | "dumb" => ColorDepth::Mono,
+        "linux" => ColorDepth::Ansi16,
+        term if term.contains("direct") || term.contains("truecolor") => ColorDepth::Truecolor,
+        term if term.contains("256color") => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_to_rgb(index)),
+        named => ANSI_16.iter().find(|&&(c, _)| c == named).map(|&(_, rgb)| rgb),
+    }
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_16[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+// Index of the candidate closest to `rgb`, candidates without RGB values are passed over
+pub fn nearest(rgb: (u8, u8, u8), candidates: &[Color]) -> Option<usize> {
+    candidates.iter()
+        .enumerate()
+        .filter_map(|(index, &color)| to_rgb(color).map(|candidate| (index, distance(candidate, rgb))))
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(index, _)| index)
+}
+
+fn nearest_cube_level(channel: u8) -> usize {
+    (0..6).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - channel as i32).abs()).unwrap_or(0)
+}
+
+// Best of the cube and grey ramp entries, the system 16 vary too much between terminals
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let (r, g, b) = (nearest_cube_level(rgb.0), nearest_cube_level(rgb.1), nearest_cube_level(rgb.2));
+    let cube = 16 + (36 * r + 6 * g + b) as u8;
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = 232 + ((average.saturating_sub(3)) / 10).min(23) as u8;
+    if distance(indexed_to_rgb(grey), rgb) < distance(indexed_to_rgb(cube), rgb) { grey } else { cube }
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI_16.iter()
+        .min_by_key(|&&(_, candidate)| distance(candidate, rgb))
+        .map_or(Color::White, |&(color, _)| color)
+}
+
+// Nearest colour the terminal can show, Reset and already fitting colours pass through
+pub fn quantize(color: Color, depth: ColorDepth) -> Color {
+    match (depth, color) {
+        (_, Color::Reset) | (ColorDepth::Auto | ColorDepth::Truecolor, _) => color,
+        (ColorDepth::Mono, _) => Color::Reset,
+        (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+        (ColorDepth::Ansi256, _) => color,
+        (ColorDepth::Ansi16, Color::Rgb(..) | Color::Indexed(16..)) => to_rgb(color).map_or(color, nearest_16),
+        (ColorDepth::Ansi16, Color::Indexed(index)) => ANSI_16[index as usize].0,
+        (ColorDepth::Ansi16, _) => color,
+    }
+}
+
+// Runs over a drawn frame, mono shows backgrounds as reversed cells instead
+pub fn quantize_buffer(buffer: &mut Buffer, depth: ColorDepth) {
+    if matches!(depth, ColorDepth::Auto | ColorDepth::Truecolor) {
+        return;
+    }
+    for cell in buffer.content.iter_mut() {
+        if depth == ColorDepth::Mono && cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = quantize(cell.fg, depth);
+        cell.bg = quantize(cell.bg, depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_follows_the_environment() {
+        assert_eq!(detect_depth(Some("truecolor"), Some("xterm")), ColorDepth::Truecolor);
+        assert_eq!(detect_depth(None, Some("tmux-256color")), ColorDepth::Ansi256);
+        assert_eq!(detect_depth(None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(detect_depth(None, Some("xterm-direct")), ColorDepth::Truecolor);
+        assert_eq!(detect_depth(None, None), ColorDepth::Mono);
+        assert_eq!(effective_depth(ColorDepth::Ansi256), ColorDepth::Ansi256);
+    }
+
+    #[test]
+    fn colours_map_to_their_nearest_neighbour() {
+        assert_eq!(quantize(Color::Rgb(255, 0, 0), ColorDepth::Ansi256), Color::Indexed(196));
+        assert_eq!(quantize(Color::Rgb(128, 128, 128), ColorDepth::Ansi256), Color::Indexed(244));
+        assert_eq!(quantize(Color::Rgb(250, 10, 10), ColorDepth::Ansi16), Color::LightRed);
+        assert_eq!(quantize(Color::Indexed(21), ColorDepth::Ansi16), Color::Blue);
+        assert_eq!(quantize(Color::Indexed(9), ColorDepth::Ansi16), Color::LightRed);
+        assert_eq!(quantize(Color::Rgb(1, 2, 3), ColorDepth::Mono), Color::Reset);
+        assert_eq!(quantize(Color::Rgb(1, 2, 3), ColorDepth::Truecolor), Color::Rgb(1, 2, 3));
+        assert_eq!(to_rgb(Color::Indexed(231)), Some((255, 255, 255)));
+        assert_eq!(to_rgb(Color::Indexed(232)), Some((8, 8, 8)));
+    }
+}
@@ -1,8 +1,10 @@
-use ratatui::style::Color;
+use ratatui::{buffer::Buffer, layout::Rect, style::Color};
+use super::color::to_rgb;
 
+// Indexed and named colours are blended through their RGB values
 pub fn fade_color_to_target(original: Color, target: Color, fade_factor: f32) -> Color {
-    match (original, target) {
-        (Color::Rgb(or, og, ob), Color::Rgb(tr, tg, tb)) => {
+    match (to_rgb(original), to_rgb(target)) {
+        (Some((or, og, ob)), Some((tr, tg, tb))) => {
             let r = (or as f32 * (1.0 - fade_factor) + tr as f32 * fade_factor) as u8;
             let g = (og as f32 * (1.0 - fade_factor) + tg as f32 * fade_factor) as u8;
             let b = (ob as f32 * (1.0 - fade_factor) + tb as f32 * fade_factor) as u8;
@@ -10,4 +12,59 @@ pub fn fade_color_to_target(original: Color, target: Color, fade_factor: f32) ->
         }
         _ => original,
     }
-}
\ No newline at end of file
+}
+
+// Drawn cells blended towards the background, for fading a whole grid out
+pub fn fade_area(buffer: &mut Buffer, area: Rect, background: Color, level: f32) {
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buffer[(x, y)];
+            cell.fg = fade_color_to_target(cell.fg, background, level);
+            if cell.bg != Color::Reset {
+                cell.bg = fade_color_to_target(cell.bg, background, level);
+            }
+        }
+    }
+}
+
+// Position of a visit count between none and the busiest cell, on a log scale
+pub fn heat_level(visits: u32, max_visits: u32) -> f32 {
+    if max_visits == 0 {
+        return 0.0;
+    }
+    ((visits as f32).ln_1p() / (max_visits as f32).ln_1p()).clamp(0.0, 1.0)
+}
+
+// Evenly spaced stops, interpolated pairwise
+pub fn gradient_color(stops: &[Color], level: f32) -> Color {
+    match stops {
+        [] => Color::White,
+        [only] => *only,
+        _ => {
+            let scaled = level.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+            let index = (scaled as usize).min(stops.len() - 2);
+            fade_color_to_target(stops[index], stops[index + 1], scaled - index as f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_follows_log_visits() {
+        let stops = [Color::Rgb(0, 0, 0), Color::Rgb(100, 0, 0), Color::Rgb(100, 200, 0)];
+        assert_eq!(gradient_color(&stops, 0.0), Color::Rgb(0, 0, 0));
+        assert_eq!(gradient_color(&stops, 0.5), Color::Rgb(100, 0, 0));
+        assert_eq!(gradient_color(&stops, 0.75), Color::Rgb(100, 100, 0));
+        assert_eq!(gradient_color(&stops, 1.0), Color::Rgb(100, 200, 0));
+        // 256-colour stops blend too
+        assert_eq!(gradient_color(&[Color::Indexed(16), Color::Indexed(231)], 0.5), Color::Rgb(127, 127, 127));
+
+        assert_eq!(heat_level(0, 0), 0.0);
+        assert_eq!(heat_level(1000, 1000), 1.0);
+        // A cell visited a tenth as often still sits well up the scale
+        assert!(heat_level(100, 1000) > 0.6);
+    }
+}
@@ -1,84 +1,141 @@
-use ratatui::{Frame, layout::Rect, style::Color};
-use super::{App, effects};
+use ratatui::{Frame, layout::Rect, style::{Color, Style}};
+use crate::{config::Config, machine::{HeadActivity, TuringMachine}};
+use super::effects;
 
 #[inline(always)]
 fn wrap_coords(x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
     (((x % width) + width) % width, ((y % height) + height) % height)
 }
 
-pub fn render_pixel_grid(f: &mut Frame, app: &App, area: Rect) {
+// Highlights the cells where two universes disagree
+const DIFF_BACKGROUND: Color = Color::Rgb(90, 20, 40);
+
+// Backgrounds marking heads that are stuck on their own
+fn marker_background(activity: HeadActivity) -> Option<Color> {
+    match activity {
+        HeadActivity::Active => None,
+        HeadActivity::Stalled => Some(Color::Rgb(140, 30, 30)),
+        HeadActivity::Orbiting => Some(Color::Rgb(120, 100, 20)),
+        HeadActivity::Idle => Some(Color::Rgb(60, 60, 60)),
+    }
+}
+
+pub fn render_pixel_grid(f: &mut Frame, machine: &TuringMachine, config: &Config, area: Rect, heatmap: bool) {
+    let width = area.width as i32 / 2;
+    let height = area.height as i32;
+
+    if heatmap {
+        render_heatmap(f, machine, config, area, width, height);
+    } else {
+        render_tape_cells(f, machine, config, area, width, height);
+        render_trails(f, machine, config, area, width, height);
+    }
+    render_heads(f, machine, config, area, width, height);
+}
+
+// Returns the number of differing cells
+pub fn render_diff(f: &mut Frame, machine: &TuringMachine, other: &TuringMachine, area: Rect) -> usize {
     let width = area.width as i32 / 2;
     let height = area.height as i32;
+    let cells = machine.grid.diff_cells(&other.grid);
 
-    render_tape_cells(f, app, area, width, height);
-    render_trails(f, app, area, width, height);
-    render_heads(f, app, area, width, height);
+    for &(x, y) in &cells {
+        let (grid_x, grid_y) = wrap_coords(x, y, width, height);
+        let buffer_y = area.y + grid_y as u16;
+        for i in 0..2 {
+            let char_x = area.x + (grid_x * 2) as u16 + i;
+            if char_x < area.x + area.width && buffer_y < area.y + area.height {
+                f.buffer_mut()[(char_x, buffer_y)].set_bg(DIFF_BACKGROUND);
+            }
+        }
+    }
+    cells.len()
 }
 
-fn render_tape_cells(f: &mut Frame, app: &App, area: Rect, width: i32, height: i32) {
-    if !app.config.simulation.color_cells {
+fn render_tape_cells(f: &mut Frame, machine: &TuringMachine, config: &Config, area: Rect, width: i32, height: i32) {
+    if !config.simulation.color_cells {
         return;
     }
 
-    for (&(x, y), &state) in app.machine.tape() {
-        if app.config.display.should_render_cell(state) {
+    let age_color = config.display.resolve_color(&config.display.cell_age_color);
+    for (&(x, y), &state) in machine.tape() {
+        if config.display.should_render_cell(state) {
             let (grid_x, grid_y) = wrap_coords(x, y, width, height);
             let buffer_x = area.x + (grid_x * 2) as u16;
             let buffer_y = area.y + grid_y as u16;
             
-            let color = app.machine.tape_colors().get(&(x, y)).copied().unwrap_or(Color::White);
-            
-            // Use stored character if available, otherwise default
-            let display_chars = if let Some(stored_char) = app.machine.tape_chars().get(&(x, y)) {
-                stored_char.chars().collect::<Vec<_>>()
-            } else {
-                app.config.display.cell_char_data.chars.clone()
-            };
+            let mut color = machine.tape_colors().get(&(x, y)).copied().unwrap_or(Color::White);
+            // Cells fade toward the age colour over cell_age_fade steps since their last write
+            if config.display.cell_age_fade > 0
+                && let Some(age) = machine.grid.age(x, y, machine.steps)
+            {
+                let fade_factor = (age as f32 / config.display.cell_age_fade as f32).min(1.0);
+                color = effects::fade_color_to_target(color, age_color, fade_factor);
+            }
             
-            for (i, &ch) in display_chars.iter().enumerate() {
-                let char_x = buffer_x + i as u16;
-                if char_x < area.x + area.width && buffer_y < area.y + area.height {
-                    f.buffer_mut()[(char_x, buffer_y)].set_char(ch).set_fg(color);
+            // Use stored character if available, otherwise the glyph for its state
+            if let Some(stored_char) = machine.tape_chars().get(&(x, y)) {
+                for (i, ch) in stored_char.chars().enumerate() {
+                    let char_x = buffer_x + i as u16;
+                    if char_x < area.x + area.width && buffer_y < area.y + area.height {
+                        f.buffer_mut()[(char_x, buffer_y)].set_char(ch).set_fg(color);
+                    }
                 }
+            } else {
+                render_character_at_position(f, config.display.get_state_char_data(state), buffer_x, buffer_y, area, color);
             }
         }
     }
 }
 
-fn render_trails(f: &mut Frame, app: &App, area: Rect, width: i32, height: i32) {
-    for (head_index, head) in app.machine.heads.iter().enumerate() {
+// Colours every visited cell by how often heads stood on it, in place of the tape
+fn render_heatmap(f: &mut Frame, machine: &TuringMachine, config: &Config, area: Rect, width: i32, height: i32) {
+    let stops = &config.display.heatmap_stops;
+    let max_visits = machine.grid.max_visits();
+
+    for (&(x, y), &visits) in &machine.grid.visits {
+        let (grid_x, grid_y) = wrap_coords(x, y, width, height);
+        let buffer_x = area.x + (grid_x * 2) as u16;
+        let buffer_y = area.y + grid_y as u16;
+        let color = effects::gradient_color(stops, effects::heat_level(visits, max_visits));
+        render_character_at_position(f, &config.display.cell_char_data, buffer_x, buffer_y, area, color);
+    }
+}
+
+fn render_trails(f: &mut Frame, machine: &TuringMachine, config: &Config, area: Rect, width: i32, height: i32) {
+    for (head_index, head) in machine.heads.iter().enumerate() {
         for (trail_index, &(trail_x, trail_y)) in head.trail.iter().rev().enumerate() {
             let (grid_x, grid_y) = wrap_coords(trail_x, trail_y, width, height);
             let buffer_x = area.x + (grid_x * 2) as u16;
             let buffer_y = area.y + grid_y as u16;
             
-            let trail_char_data = if app.config.display.direction_based_chars {
+            let trail_char_data = if config.display.direction_based_chars {
                 // Use stored cell character if available
-                if let Some(stored_char) = app.machine.tape_chars().get(&(trail_x, trail_y)) {
-                    if let Some(index) = app.config.display.head_char.iter().position(|c| c == stored_char) {
-                        &app.config.display.head_char_data[index]
+                if let Some(stored_char) = machine.tape_chars().get(&(trail_x, trail_y)) {
+                    if let Some(index) = config.display.direction_glyphs.iter().position(|c| c == stored_char) {
+                        &config.display.direction_char_data[index]
                     } else {
-                        &app.config.display.trail_char_data[0]
+                        &config.display.trail_char_data[0]
                     }
                 } else {
-                    &app.config.display.trail_char_data[0]
+                    &config.display.trail_char_data[0]
                 }
             } else {
                 // Regular trail logic
-                let char_index = if app.config.display.randomize_trails {
-                    let random_index = app.machine.get_trail_char_index(head_index, trail_index);
-                    random_index % app.config.display.trail_char_data.len()
-                } else if trail_index < app.config.display.trail_char_data.len() {
+                let char_index = if config.display.randomize_trails {
+                    let random_index = machine.get_trail_char_index(head_index, trail_index);
+                    random_index % config.display.trail_char_data.len()
+                } else if trail_index < config.display.trail_char_data.len() {
                     trail_index
                 } else {
-                    app.config.display.trail_char_data.len() - 1
+                    config.display.trail_char_data.len() - 1
                 };
-                &app.config.display.trail_char_data[char_index]
+                &config.display.trail_char_data[char_index]
             };
             
-            let color = if !app.config.display.fade_trail_color.is_empty() {
-                let fade_factor = trail_index as f32 / app.config.simulation.trail_length as f32;
-                let target_color = app.config.parse_color(&app.config.display.fade_trail_color);
+            let color = if !config.display.fade_trail_color.is_empty() {
+                let fade_factor = trail_index as f32 / config.simulation.trail_length as f32;
+                let target_color = config.display.resolve_color(&config.display.fade_trail_color);
                 effects::fade_color_to_target(head.color, target_color, fade_factor)
             } else {
                 head.color
@@ -89,8 +146,8 @@ fn render_trails(f: &mut Frame, app: &App, area: Rect, width: i32, height: i32)
     }
 }
 
-fn render_heads(f: &mut Frame, app: &App, area: Rect, width: i32, height: i32) {
-    for (head_index, head) in app.machine.heads.iter().enumerate() {
+fn render_heads(f: &mut Frame, machine: &TuringMachine, config: &Config, area: Rect, width: i32, height: i32) {
+    for (head_index, head) in machine.heads.iter().enumerate() {
         let (grid_x, grid_y) = wrap_coords(head.x, head.y, width, height);
         let buffer_x = area.x + (grid_x * 2) as u16;
         let buffer_y = area.y + grid_y as u16;
@@ -103,16 +160,31 @@ fn render_heads(f: &mut Frame, app: &App, area: Rect, width: i32, height: i32) {
             }
         }
         
-        let char_index = app.machine.get_head_char_index(head_index, &app.config);
-        
-        let head_char_data = &app.config.display.head_char_data[char_index];
+        let head_char_data = machine.get_head_char_data(head_index, config);
         render_character_at_position(f, head_char_data, buffer_x, buffer_y, area, head.color);
+
+        if config.display.head_markers
+            && let Some(background) = marker_background(head.watch.activity())
+        {
+            for i in 0..2 {
+                let char_x = buffer_x + i as u16;
+                if char_x < area.x + area.width && buffer_y < area.y + area.height {
+                    f.buffer_mut()[(char_x, buffer_y)].set_bg(background);
+                }
+            }
+        }
     }
 }
 
 
 fn render_character_at_position(f: &mut Frame, char_data: &crate::config::CharData, buffer_x: u16, buffer_y: u16, area: Rect, color: Color) {
-    if char_data.is_single_char {
+    if char_data.is_wide {
+        // Needs both columns, so it is left out at the right edge
+        if buffer_x + 1 < area.x + area.width && buffer_y < area.y + area.height {
+            let mut glyph = [0u8; 4];
+            f.buffer_mut().set_string(buffer_x, buffer_y, char_data.chars[0].encode_utf8(&mut glyph), Style::default().fg(color));
+        }
+    } else if char_data.is_single_char {
         if buffer_x < area.x + area.width && buffer_y < area.y + area.height {
             f.buffer_mut()[(buffer_x, buffer_y)].set_char(' ');
         }
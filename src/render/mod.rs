@@ -1,18 +1,51 @@
 pub mod grid;
 pub mod effects;
+pub mod color;
+pub mod background;
+pub mod slideshow;
 pub mod ui;
 
-use ratatui::Frame;
-use crate::{machine::TuringMachine, config::{Config, SimMode}};
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{Frame, layout::Rect};
+use crate::{machine::{Grid, TuringMachine, rules::Direction}, config::{ColorDepth, Config, CompareConfig, Reaction, glyphs, history::{History, RunRecord}, presets::{self, Preset}}};
+use slideshow::Slideshow;
 use std::time::Duration;
 
 pub struct App {
     pub machine: TuringMachine,
+    // Comparison machines tiled beside the main one
+    pub universes: Vec<TuringMachine>,
+    pub show_diff: bool,
+    // Last rule played from the playlist
+    pub playlist_position: Option<usize>,
+    pub slideshow: Option<Slideshow>,
     pub last_step: std::time::Instant,
     pub step_interval: Duration,
     pub config: Config,
     pub show_help: bool,
     pub show_statusbar: bool,
+    pub show_stats: bool,
+    pub show_heatmap: bool,
+    // What the terminal can show, colours are quantised to it after drawing
+    pub color_depth: ColorDepth,
+    // Mouse edits the main universe: left paints, right places heads, hovering inspects
+    pub mouse_mode: bool,
+    pub paint_state: char,
+    pub head_direction: Direction,
+    // Grid cell under the pointer
+    pub hover: Option<(i32, i32)>,
+    // Preset browser, filled from disk each time it opens
+    pub show_presets: bool,
+    pub presets: Vec<Preset>,
+    pub preset_errors: Vec<String>,
+    pub preset_selection: usize,
+    // Runs played, read from disk the first time they're needed
+    pub history: Option<History>,
+    pub show_history: bool,
+    // Counts back from the newest run
+    pub history_selection: usize,
+    // Run being replayed from history, None on a fresh run
+    pub history_position: Option<usize>,
     pub error_message: Option<String>,
     pub last_keypress: Option<String>,
     pub keypress_time: Option<std::time::Instant>
@@ -20,23 +53,448 @@ pub struct App {
 
 impl App {
     pub fn new(config: Config) -> Self {
-        Self {
+        let mut app = Self {
             machine: TuringMachine::new(
                 config.simulation.heads,
                 &config.simulation.rule,
                 &config
             ),
+            universes: Vec::new(),
+            show_diff: config.compare.diff,
+            playlist_position: None,
+            slideshow: None,
             last_step: std::time::Instant::now(),
             step_interval: Duration::from_nanos((config.simulation.speed_ms * 1_000_000.0) as u64),
-            config,
             show_help: false,
             show_statusbar: false,
+            show_stats: false,
+            show_heatmap: false,
+            color_depth: color::effective_depth(config.display.color_depth),
+            mouse_mode: false,
+            paint_state: 'B',
+            head_direction: Direction::Up,
+            hover: None,
+            show_presets: false,
+            presets: Vec::new(),
+            preset_errors: Vec::new(),
+            preset_selection: 0,
+            history: None,
+            show_history: false,
+            history_selection: 0,
+            history_position: None,
+            config,
             error_message: None,
             last_keypress: None,
             keypress_time: None,
+        };
+        app.rebuild_universes();
+        app.start_slideshow();
+        app
+    }
+
+    // Starts a run that stays out of the state files, like one from --preset
+    pub fn run_unsaved(&mut self, seed: Option<&str>, rule: &str) {
+        self.machine.pin(seed, rule);
+        self.machine.reset_clean(&self.config);
+        self.rebuild_universes();
+    }
+
+    pub fn machines(&self) -> impl Iterator<Item = &TuringMachine> {
+        std::iter::once(&self.machine).chain(self.universes.iter())
+    }
+
+    fn machines_mut(&mut self) -> impl Iterator<Item = &mut TuringMachine> {
+        std::iter::once(&mut self.machine).chain(self.universes.iter_mut())
+    }
+
+    // Respawn the comparison universes from the main machine's seed, rule and heads
+    pub fn rebuild_universes(&mut self) {
+        self.universes.clear();
+        for index in 1..self.config.compare.universes {
+            let (seed, rule, heads) = self.config.compare.variant(
+                index,
+                &self.machine.current_seed,
+                &self.machine.rule_string,
+                self.machine.num_heads,
+            );
+            let mut universe = TuringMachine::pinned(heads, &seed, &rule, &self.config);
+            universe.running = self.machine.running;
+            self.universes.push(universe);
+        }
+    }
+
+    pub fn cycle_universes(&mut self) {
+        self.config.compare.universes = self.config.compare.universes % CompareConfig::MAX_UNIVERSES + 1;
+        self.rebuild_universes();
+    }
+
+    // Pause and resume are shared across universes
+    pub fn toggle_running(&mut self) {
+        self.machine.toggle_running();
+        let running = self.machine.running;
+        for universe in &mut self.universes {
+            universe.running = running;
+        }
+    }
+
+    pub fn step_once(&mut self, area: Rect) {
+        let layout = ui::universe_layout(area, 1 + self.universes.len());
+        let config = &self.config;
+        for (machine, (grid_area, _)) in std::iter::once(&mut self.machine).chain(self.universes.iter_mut()).zip(layout) {
+            machine.step(grid_area.width as i32 / 2, grid_area.height as i32, config);
+            machine.mark_trail_dirty();
+        }
+    }
+
+    // Fresh random seed and/or rule, kept in state like the other runtime parameters
+    pub fn randomize(&mut self, seed: bool, rule: bool) {
+        self.record_run();
+        self.machine.release();
+        let what = match (seed, rule) {
+            (true, false) => "seed",
+            (false, true) => "rule",
+            _ => "parameters",
+        };
+        let saved = if seed { Config::save_current_seed(&self.machine.generate_random_seed()) } else { Ok(()) }
+            .and_then(|_| if rule { Config::save_current_rule(&Config::generate_random_rule()) } else { Ok(()) });
+        match saved {
+            Ok(()) => {
+                self.machine.reset_clean(&self.config);
+                self.rebuild_universes();
+            }
+            Err(e) => self.show_error(format!("Failed to save random {}: {}", what, e)),
+        }
+    }
+
+    // A slideshow is the playlist when there is one, so next_rule moves on to its next entry
+    pub fn next_playlist_rule(&mut self) {
+        if self.slideshow.is_some() {
+            self.skip_entry(1);
+            return;
+        }
+        let Some((index, rule)) = self.config.playlist.next(self.playlist_position).map(|(i, r)| (i, r.to_string())) else {
+            return;
+        };
+        self.record_run();
+        self.machine.release();
+        match Config::save_current_rule(&rule) {
+            Ok(()) => {
+                self.playlist_position = Some(index);
+                self.machine.reset_clean(&self.config);
+                self.rebuild_universes();
+            }
+            Err(e) => self.show_error(format!("Failed to save playlist rule: {}", e)),
+        }
+    }
+
+    // Starts playlist.entries over from the first, under the config as it is now
+    pub fn start_slideshow(&mut self) {
+        self.slideshow = Slideshow::new(self.config.clone());
+        if self.slideshow.is_some() {
+            self.load_entry(0);
+        }
+    }
+
+    // Entries run pinned, so a slideshow never writes the state files. It keeps the config it started from
+    fn load_entry(&mut self, index: usize) {
+        let Some(slideshow) = self.slideshow.as_mut() else {
+            return;
+        };
+        slideshow.started(index);
+        let entry = &slideshow.base.playlist.entries[index];
+        match entry.apply(&slideshow.base) {
+            Ok(config) => {
+                let rule = match config.simulation.rule.as_str() {
+                    "" => Config::generate_random_rule(),
+                    rule => rule.to_string(),
+                };
+                self.machine.pin(config.simulation.seed.as_deref(), &rule);
+                self.apply_config(config);
+                // Slides keep playing regardless of autoplay
+                self.machine.running = true;
+                for universe in &mut self.universes {
+                    universe.running = true;
+                }
+            }
+            Err(e) => self.show_error(format!("Failed to load playlist entry {}: {}", index + 1, e)),
+        }
+    }
+
+    pub fn skip_entry(&mut self, offset: isize) {
+        if let Some(index) = self.slideshow.as_mut().and_then(|slideshow| slideshow.skip(offset)) {
+            self.load_entry(index);
+        }
+    }
+
+    // Runs picked by hand stay on screen until the slideshow is resumed
+    fn pause_slideshow(&mut self) {
+        if let Some(slideshow) = self.slideshow.as_mut() {
+            slideshow.pause();
+        }
+    }
+
+    pub fn toggle_slideshow_pause(&mut self) {
+        if let Some(slideshow) = self.slideshow.as_mut() {
+            slideshow.toggle_pause();
         }
     }
 
+    pub fn toggle_mouse_mode(&mut self) {
+        self.mouse_mode = !self.mouse_mode;
+        self.hover = None;
+    }
+
+    // Steps through the states the rule uses, blank erases
+    pub fn cycle_paint_state(&mut self) {
+        let states = self.machine.palette_span().states.max(2) as u8;
+        let next = (self.paint_state as u8).saturating_sub(b'A') + 1;
+        self.paint_state = (b'A' + next % states) as char;
+    }
+
+    // Eighth turns, clockwise for positive steps
+    pub fn rotate_head_direction(&mut self, steps: isize) {
+        let index = glyphs::direction_index(self.head_direction) as isize + steps;
+        self.head_direction = glyphs::DIRECTION_ORDER[index.rem_euclid(8) as usize];
+    }
+
+    // Pointer events over the main universe, cells are two columns wide
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, area: Rect) {
+        if !self.mouse_mode {
+            return;
+        }
+        let (grid_area, _) = ui::universe_layout(area, 1 + self.universes.len())[0];
+        let inside = mouse.column >= grid_area.x && mouse.row >= grid_area.y
+            && mouse.column < grid_area.x + grid_area.width && mouse.row < grid_area.y + grid_area.height;
+        let cell = inside.then(|| (
+            ((mouse.column - grid_area.x) / 2) as i32,
+            (mouse.row - grid_area.y) as i32,
+        ));
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.rotate_head_direction(-1),
+            MouseEventKind::ScrollDown => self.rotate_head_direction(1),
+            MouseEventKind::Moved | MouseEventKind::Down(MouseButton::Middle) => self.hover = cell,
+            _ => {}
+        }
+        let Some((x, y)) = cell else {
+            return;
+        };
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+                self.machine.paint_cell(x, y, self.paint_state, &self.config);
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                self.machine.place_head(x, y, self.head_direction, &self.config);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn paint_state_name(&self) -> String {
+        if self.paint_state == Grid::EMPTY { "blank".to_string() } else { self.paint_state.to_string() }
+    }
+
+    pub fn toggle_presets(&mut self) {
+        if self.show_presets {
+            self.show_presets = false;
+        } else {
+            let (presets, errors) = presets::load_all();
+            self.open_presets(presets, errors);
+        }
+    }
+
+    // Opens the browser on a list that's already loaded, keeping the selection where it fits
+    fn open_presets(&mut self, presets: Vec<Preset>, errors: Vec<String>) {
+        (self.presets, self.preset_errors) = (presets, errors);
+        self.preset_selection = self.preset_selection.min(self.presets.len().saturating_sub(1));
+        self.show_presets = true;
+    }
+
+    // Keys while the browser is open, true when the browser used the key
+    pub fn preset_key(&mut self, code: KeyCode) -> bool {
+        if !self.show_presets {
+            return false;
+        }
+        let count = self.presets.len().max(1);
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.preset_selection = (self.preset_selection + count - 1) % count,
+            KeyCode::Down | KeyCode::Char('j') => self.preset_selection = (self.preset_selection + 1) % count,
+            KeyCode::Enter => {
+                if let Some(preset) = self.presets.get(self.preset_selection).cloned() {
+                    self.load_preset(&preset);
+                }
+            }
+            KeyCode::Esc => self.show_presets = false,
+            _ => return false,
+        }
+        true
+    }
+
+    // Rule and seed go to state like a random pick does, so they survive restarts
+    pub fn load_preset(&mut self, preset: &Preset) {
+        self.pause_slideshow();
+        self.machine.release();
+        let loaded = preset.apply(&self.config)
+            .and_then(|config| config.save_preset_state().map(|_| config).map_err(|e| e.to_string()));
+        match loaded {
+            Ok(config) => {
+                self.apply_config(config);
+                self.show_presets = false;
+            }
+            Err(e) => self.show_error(format!("Failed to load preset {}: {}", preset.name, e)),
+        }
+    }
+
+    // Swaps in a new config and restarts every universe under it
+    pub fn apply_config(&mut self, config: Config) {
+        self.record_run();
+        let background = self.config.display.background;
+        self.config = config;
+        self.config.display.cache_char_data();
+        resolve_background(&mut self.config, background);
+        self.color_depth = color::effective_depth(self.config.display.color_depth);
+        self.machine.set_head_count(self.config.simulation.heads, &self.config);
+        self.step_interval = Duration::from_nanos((self.config.simulation.speed_ms * 1_000_000.0) as u64);
+
+        self.machine.update_colors(&self.config);
+        self.machine.reset(&self.config);
+        self.rebuild_universes();
+        self.show_diff = self.config.compare.diff;
+        self.error_message = None;
+    }
+
+    fn history(&mut self) -> &mut History {
+        self.history.get_or_insert_with(History::load)
+    }
+
+    // Adds the run on screen to the history and returns where it went
+    fn log_run(&mut self) -> usize {
+        self.machine.poll_onset();
+        let run = RunRecord {
+            rule: self.machine.rule_string.clone(),
+            seed: self.machine.current_seed.clone(),
+            heads: self.machine.num_heads,
+            steps: self.machine.steps,
+            outcome: self.machine.detector.status().to_string(),
+            favourite: false,
+        };
+        let history = self.history();
+        history.record(run);
+        let index = history.runs.len() - 1;
+        if let Err(e) = history.save() {
+            self.show_error(format!("Failed to save history: {}", e));
+        }
+        index
+    }
+
+    // Called before a run is replaced. Replays are already in the history
+    pub fn record_run(&mut self) {
+        if self.history_position.take().is_none() && self.machine.steps > 0 {
+            self.log_run();
+        }
+    }
+
+    pub fn toggle_favourite(&mut self) {
+        let index = match self.history_position {
+            Some(index) => index,
+            None => self.log_run(),
+        };
+        self.history().toggle_favourite(index);
+        if let Err(e) = self.history().save() {
+            self.show_error(format!("Failed to save history: {}", e));
+        }
+    }
+
+    pub fn is_favourite(&self) -> bool {
+        let history = self.history.as_ref();
+        match self.history_position {
+            Some(index) => history.and_then(|h| h.runs.get(index)).is_some_and(|run| run.favourite),
+            // A fresh run only has an entry once it's been bookmarked
+            None => history.and_then(|h| h.runs.last()).is_some_and(|run| {
+                run.favourite && (&run.rule, &run.seed, run.heads)
+                    == (&self.machine.rule_string, &self.machine.current_seed, self.machine.num_heads)
+            }),
+        }
+    }
+
+    // Rule, seed and head count go to state so the replay survives restarts
+    pub fn replay_run(&mut self, index: usize) {
+        let Some(run) = self.history().runs.get(index).cloned() else {
+            return;
+        };
+        self.pause_slideshow();
+        self.machine.release();
+        match Config::save_current_rule(&run.rule).and_then(|_| Config::save_current_seed(&run.seed)) {
+            Ok(()) => {
+                self.history_position = Some(index);
+                self.machine.num_heads = run.heads.min(256);
+                self.machine.reset_clean(&self.config);
+                self.rebuild_universes();
+            }
+            Err(e) => self.show_error(format!("Failed to replay run: {}", e)),
+        }
+    }
+
+    // Walks the history from the run on screen, logging a fresh run first so it can be returned to
+    pub fn walk_history(&mut self, back: bool) {
+        let current = match self.history_position {
+            Some(index) => index,
+            None if self.machine.steps > 0 => self.log_run(),
+            None => self.history().runs.len(),
+        };
+        let target = if back { current.checked_sub(1) } else { Some(current + 1) };
+        if let Some(target) = target.filter(|&index| index < self.history().runs.len()) {
+            self.replay_run(target);
+        }
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.show_history = !self.show_history;
+        if self.show_history {
+            let count = self.history().runs.len();
+            self.history_selection = match self.history_position {
+                Some(index) => count.saturating_sub(index + 1),
+                None => 0,
+            };
+        }
+    }
+
+    // Keys while the history is open, true when the overlay used the key
+    pub fn history_key(&mut self, code: KeyCode) -> bool {
+        if !self.show_history {
+            return false;
+        }
+        let count = self.history().runs.len();
+        let selected = count.checked_sub(self.history_selection + 1);
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.history_selection = self.history_selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.history_selection = (self.history_selection + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                if let Some(index) = selected {
+                    // Logging the run on screen can drop the oldest entry, so find the pick again
+                    let chosen = self.history().runs[index].clone();
+                    self.record_run();
+                    let index = self.history().runs.iter().rposition(|run| *run == chosen).unwrap_or(index);
+                    self.replay_run(index);
+                    self.show_history = false;
+                }
+            }
+            KeyCode::Char(ch) if ch.to_string() == self.config.controls.favourite => {
+                if let Some(index) = selected {
+                    self.history().toggle_favourite(index);
+                    if let Err(e) = self.history().save() {
+                        self.show_error(format!("Failed to save history: {}", e));
+                    }
+                }
+            }
+            KeyCode::Esc => self.show_history = false,
+            _ => return false,
+        }
+        true
+    }
+
     pub fn show_error(&mut self, message: String) {
         self.error_message = Some(message);
     }
@@ -44,6 +502,9 @@ impl App {
     pub fn clear_overlays(&mut self) {
         self.show_help = false;
         self.show_statusbar = false;
+        self.show_stats = false;
+        self.show_presets = false;
+        self.show_history = false;
         self.error_message = None;
     }
 
@@ -67,67 +528,184 @@ impl App {
         }
     }
 
-    pub fn update(&mut self, width: i32, height: i32) {
-        // Update grid dimensions
-        self.machine.update_grid_dimensions(width, height);
+    pub fn update(&mut self, area: Rect) {
+        if let Some(error) = self.machine.rule_error.take() {
+            self.show_error(format!("Invalid rule '{}': {}", self.machine.rule_string, error));
+        }
+        let layout = ui::universe_layout(area, 1 + self.universes.len());
+        let step_due = self.last_step.elapsed() >= self.step_interval;
+        let steps_per_frame = if self.step_interval < Duration::from_millis(16) {
+            (Duration::from_millis(16).as_nanos() / self.step_interval.as_nanos().max(1)) as usize
+        } else {
+            1
+        };
         
-        if self.machine.running && self.last_step.elapsed() >= self.step_interval {
-            let steps_per_frame = if self.step_interval < Duration::from_millis(16) {
-                (Duration::from_millis(16).as_nanos() / self.step_interval.as_nanos().max(1)) as usize
-            } else {
-                1
-            };
+        self.machine.stats.track_visits(self.show_stats, self.machine.steps);
+        let heatmap = self.show_heatmap;
+        let config = &self.config;
+        let mut stepped = false;
+        for (machine, (grid_area, _)) in std::iter::once(&mut self.machine).chain(self.universes.iter_mut()).zip(layout) {
+            let (width, height) = (grid_area.width as i32 / 2, grid_area.height as i32);
+            // Update grid dimensions
+            machine.update_grid_dimensions(width, height, config);
+            machine.grid.count_visits(heatmap);
             
-            for _ in 0..steps_per_frame.min(100) {
-                self.machine.step(width, height, &self.config);
-                if self.machine.detection_pending() {
-                    break;
+            if machine.running && step_due {
+                for _ in 0..steps_per_frame.min(100) {
+                    machine.step(width, height, config);
+                    if machine.pending_outcome(&config.reactions).is_some() {
+                        break;
+                    }
                 }
+                
+                machine.mark_trail_dirty();
+                stepped = true;
             }
-            
-            self.machine.mark_trail_dirty();
+            machine.poll_onset();
+        }
+        if stepped {
             self.last_step = std::time::Instant::now();
         }
 
-        if self.machine.running {
+        if let Some(index) = self.slideshow.as_mut().and_then(|slideshow| slideshow.tick(&self.machine)) {
+            self.load_entry(index);
+        }
+        // A fading grid is on its way out, reacting would only restart it
+        if self.slideshow.as_ref().is_none_or(|slideshow| slideshow.fade_level().is_none()) {
             self.apply_mode_reaction();
         }
     }
 
     fn apply_mode_reaction(&mut self) {
-        if !self.machine.detection_pending() {
-            return;
+        let config = &self.config;
+        let reaction = |machine: &TuringMachine| {
+            machine.pending_outcome(&config.reactions)
+                .filter(|_| machine.running)
+                .and_then(|outcome| config.reactions.for_outcome(outcome, &config.simulation))
+        };
+
+        // Comparison universes keep their seed and rule, so anything new just replays them
+        let mut errors = Vec::new();
+        for index in 0..self.universes.len() {
+            let universe = &self.universes[index];
+            match reaction(universe) {
+                None => {}
+                Some(Reaction::Halt) => self.universes[index].auto_halt(),
+                Some(Reaction::Snapshot) => {
+                    errors.extend(save_snapshot(universe, config).err());
+                    self.universes[index].auto_halt();
+                }
+                Some(_) => self.universes[index].restart_replay(&self.config),
+            }
         }
-        match self.config.simulation.mode {
-            SimMode::Loop => self.machine.restart_replay(&self.config),
-            SimMode::Halt => self.machine.auto_halt(),
+
+        match reaction(&self.machine) {
+            None => {}
+            Some(Reaction::Halt) => self.machine.auto_halt(),
+            Some(Reaction::Restart) => self.machine.restart_replay(&self.config),
+            Some(Reaction::Snapshot) => {
+                errors.extend(save_snapshot(&self.machine, &self.config).err());
+                self.machine.auto_halt();
+            }
+            Some(fresh) => {
+                match fresh {
+                    Reaction::NewSeed => self.randomize(true, false),
+                    Reaction::NewRule => self.randomize(false, true),
+                    _ => self.next_playlist_rule(),
+                }
+                // Machine-initiated runs keep going regardless of autoplay
+                self.machine.running = true;
+                for universe in &mut self.universes {
+                    universe.running = true;
+                }
+            }
+        }
+
+        if let Some(error) = errors.pop() {
+            self.show_error(error);
         }
     }
 }
 
+// The terminal is asked once at startup when the config uses "auto", before the event loop
+// reads input. Every config after that reuses the answer, also when there was none
+pub fn resolve_background(config: &mut Config, terminal: Option<ratatui::style::Color>) {
+    config.display.background = terminal;
+    config.display.cache_palette();
+}
+
+// With state_chars set, a drawing of the tape in those glyphs is saved next to it, and so is the heatmap
+fn save_snapshot(machine: &TuringMachine, config: &Config) -> Result<std::path::PathBuf, String> {
+    let name = format!("{}-{}", machine.current_seed, machine.steps);
+    if !config.display.state_chars.is_empty() {
+        Config::save_snapshot(&format!("{}-glyphs", name), &machine.glyph_text(config))
+            .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+    }
+    // Visits are only there while the heatmap is shown
+    if machine.grid.counts_visits() {
+        Config::save_snapshot(&format!("{}-heatmap", name), &machine.heatmap_text())
+            .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+    }
+    Config::save_snapshot(&name, &machine.snapshot_text())
+        .map_err(|e| format!("Failed to save snapshot: {}", e))
+}
+
 pub fn ui(f: &mut Frame, app: &mut App) {
-    grid::render_pixel_grid(f, app, f.area());
+    let layout = ui::universe_layout(f.area(), 1 + app.universes.len());
+    for (index, (machine, (grid_area, label_area))) in app.machines().zip(layout).enumerate() {
+        grid::render_pixel_grid(f, machine, &app.config, grid_area, app.show_heatmap);
+        
+        // Main universe is compared with the first comparison, the rest with the main one
+        let other = if index == 0 { app.universes.first() } else { Some(&app.machine) };
+        let diff = match other {
+            Some(other) if app.show_diff => Some(grid::render_diff(f, machine, other, grid_area)),
+            _ => None,
+        };
+        if let Some(label_area) = label_area {
+            ui::render_universe_label(f, machine, index, diff, label_area);
+        }
+    }
+
+    if let Some(level) = app.slideshow.as_ref().and_then(Slideshow::fade_level) {
+        let background = app.config.display.background.unwrap_or(background::FALLBACK_BACKGROUND);
+        let area = f.area();
+        effects::fade_area(f.buffer_mut(), area, background, level);
+    }
 
     if app.should_show_keycast() {
         ui::render_keycast_overlay(f, app);
     }
+    if app.mouse_mode {
+        ui::render_mouse_overlay(f, app);
+    }
 
     // Render overlays
+    if app.show_stats {
+        ui::render_stats_overlay(f, app);
+    }
     if let Some(ref error) = app.error_message {
         ui::render_error_overlay(f, app, error);
     } else if app.show_statusbar {
         ui::render_statusbar_overlay(f, app);
+    } else if app.show_presets {
+        ui::render_preset_overlay(f, app);
+    } else if app.show_history {
+        ui::render_history_overlay(f, app);
     } else if app.show_help {
         ui::render_help_overlay(f, app);
     }
+    color::quantize_buffer(f.buffer_mut(), app.color_depth);
     
-    app.machine.clear_dirty_cells();
+    for machine in app.machines_mut() {
+        machine.clear_dirty_cells();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::machine::DetectionStatus;
+    use crate::config::SimMode;
 
     #[test]
     fn no_reaction_while_detector_running() {
@@ -169,4 +747,90 @@ mod tests {
         assert!(app.machine.auto_halted);
         assert_eq!(app.machine.detector.status(), DetectionStatus::Stalled { at_step: 10 });
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn outcome_reactions_override_the_mode() {
+        let mut config = Config::default();
+        config.simulation.mode = SimMode::Loop;
+        config.reactions.max_steps = 5;
+        config.reactions.on_timeout = Some(Reaction::Halt);
+        let mut app = App::new(config);
+        app.machine.running = true;
+        app.machine.has_looped = true;
+        app.machine.steps = 5;
+        app.apply_mode_reaction();
+        assert!(!app.machine.running);
+        assert!(app.machine.auto_halted);
+
+        // Stalls still follow the loop mode
+        app.machine.auto_halted = false;
+        app.machine.running = true;
+        app.machine.steps = 3;
+        app.machine.detector.mark_stalled(3);
+        app.apply_mode_reaction();
+        assert_eq!(app.machine.steps, 0);
+        assert!(app.machine.running);
+    }
+
+    #[test]
+    fn preset_browser_takes_keys_only_while_open() {
+        let mut app = App::new(Config::default());
+        assert!(!app.preset_key(KeyCode::Down));
+        // Built-ins only, so presets in the real config dir don't leak in
+        app.open_presets(presets::builtin(), Vec::new());
+        assert!(app.presets.len() >= 8);
+        assert!(app.preset_key(KeyCode::Up));
+        assert_eq!(app.preset_selection, app.presets.len() - 1);
+        assert!(app.preset_key(KeyCode::Char('j')));
+        assert_eq!(app.preset_selection, 0);
+        assert!(!app.preset_key(KeyCode::Char('q')));
+        assert!(app.preset_key(KeyCode::Esc));
+        assert!(!app.show_presets);
+    }
+
+    #[test]
+    fn next_rule_moves_a_slideshow_on_as_a_pinned_run() {
+        let entry = |rule: &str, seed: &str| crate::config::playlist::PlaylistEntry {
+            rule: rule.to_string(),
+            seed: seed.to_string(),
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        config.playlist.rules = vec!["LLRR".to_string()];
+        config.playlist.entries = vec![entry("RL", "first"), entry("RLR", "second")];
+        config.playlist.transition = crate::config::Transition::Cut;
+        let mut app = App::new(config);
+        let run = |app: &App| (app.machine.rule_string.clone(), app.machine.current_seed.clone());
+        assert_eq!(run(&app), ("RL".to_string(), "first".to_string()));
+
+        // Entries are the playlist, and restarting a slide replays it rather than reading state
+        app.next_playlist_rule();
+        assert_eq!(app.slideshow.as_ref().map(|s| s.position), Some(1));
+        assert_eq!(app.playlist_position, None);
+        app.machine.reset(&app.config);
+        assert_eq!(run(&app), ("RLR".to_string(), "second".to_string()));
+        app.next_playlist_rule();
+        assert_eq!(run(&app), ("RL".to_string(), "first".to_string()));
+    }
+
+    #[test]
+    fn comparison_universes_follow_the_main_machine() {
+        let mut config = Config::default();
+        config.compare.universes = 3;
+        config.compare.vary = crate::config::compare::Variation::Heads;
+        let mut app = App::new(config);
+        let heads: Vec<usize> = app.machines().map(|m| m.num_heads).collect();
+        let base = heads[0];
+        assert_eq!(heads, vec![base, base + 1, base + 2]);
+        assert!(app.universes.iter().all(|u| u.rule_string == app.machine.rule_string));
+
+        let running = app.machine.running;
+        app.toggle_running();
+        assert!(app.machines().all(|m| m.running != running));
+
+        app.cycle_universes();
+        assert_eq!(app.universes.len(), 3);
+        app.cycle_universes();
+        assert!(app.universes.is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+use crate::{config::{Config, ReactionsConfig, Transition}, machine::TuringMachine};
+
+// Plays playlist.entries in turn, each laid over the config the slideshow started from
+pub struct Slideshow {
+    pub base: Config,
+    pub position: usize,
+    started: Instant,
+    paused_at: Option<Instant>,
+    // Entry to load once the old grid has faded out, and when the fade began
+    fade: Option<(usize, Instant)>,
+}
+
+impl Slideshow {
+    pub fn new(base: Config) -> Option<Self> {
+        (!base.playlist.entries.is_empty()).then(|| Self {
+            base,
+            position: 0,
+            started: Instant::now(),
+            paused_at: None,
+            fade: None,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.base.playlist.entries.len()
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    // Time spent paused doesn't count towards the entry's limit
+    pub fn toggle_pause(&mut self) {
+        match self.paused_at.take() {
+            Some(at) => self.started += at.elapsed(),
+            None => self.paused_at = Some(Instant::now()),
+        }
+    }
+
+    // Holds the current entry, also calling off a fade to the next one
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+        self.fade = None;
+    }
+
+    pub fn started(&mut self, position: usize) {
+        self.position = position;
+        self.started = Instant::now();
+        self.fade = None;
+    }
+
+    fn fade_duration(&self) -> Option<Duration> {
+        let playlist = &self.base.playlist;
+        (playlist.transition == Transition::Fade && playlist.transition_ms > 0)
+            .then(|| Duration::from_millis(playlist.transition_ms))
+    }
+
+    // Moves by `offset` entries, returning the one to load now unless a fade comes first
+    pub fn skip(&mut self, offset: isize) -> Option<usize> {
+        let target = (self.position as isize + offset).rem_euclid(self.len() as isize) as usize;
+        if self.fade_duration().is_none() {
+            return Some(target);
+        }
+        self.fade = Some((target, Instant::now()));
+        None
+    }
+
+    // Called every frame, returns the entry to load once one is due
+    pub fn tick(&mut self, machine: &TuringMachine) -> Option<usize> {
+        if let Some((target, began)) = self.fade {
+            return (began.elapsed() >= self.fade_duration().unwrap_or_default()).then_some(target);
+        }
+        if self.paused() || !self.finished(machine, self.started.elapsed()) {
+            return None;
+        }
+        self.skip(1)
+    }
+
+    fn finished(&self, machine: &TuringMachine, elapsed: Duration) -> bool {
+        let (seconds, steps) = self.base.playlist.limits(self.position);
+        (seconds > 0.0 && elapsed.as_secs_f64() >= seconds)
+            || (steps > 0 && machine.steps >= steps)
+            // Default reactions never time out, so only detection counts. Highways follow highway_reaction
+            || (self.base.playlist.on_detection && machine.pending_outcome(&ReactionsConfig::default()).is_some())
+    }
+
+    // How far the old grid has faded, from 0 to 1
+    pub fn fade_level(&self) -> Option<f32> {
+        let (_, began) = self.fade?;
+        let duration = self.fade_duration()?;
+        Some((began.elapsed().as_secs_f32() / duration.as_secs_f32()).min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::playlist::PlaylistEntry;
+
+    fn slideshow(transition: Transition) -> Slideshow {
+        let mut config = Config::default();
+        config.playlist.entries = vec![PlaylistEntry::default(); 3];
+        config.playlist.steps = 100;
+        config.playlist.transition = transition;
+        Slideshow::new(config).unwrap()
+    }
+
+    #[test]
+    fn entries_advance_on_their_limits() {
+        assert!(Slideshow::new(Config::default()).is_none());
+        let config = Config::default();
+        let mut machine = TuringMachine::pinned(1, "seed", "RL", &config);
+        let mut show = slideshow(Transition::Cut);
+        assert_eq!(show.tick(&machine), None);
+        machine.steps = 100;
+        assert_eq!(show.tick(&machine), Some(1));
+        assert_eq!(show.skip(-1), Some(2));
+        assert!(show.finished(&TuringMachine::pinned(1, "seed", "RL", &config), Duration::from_secs(60)));
+
+        // Paused shows wait, detection ends an entry early
+        show.toggle_pause();
+        assert_eq!(show.tick(&machine), None);
+        show.toggle_pause();
+        machine.steps = 0;
+        machine.detector.mark_stalled(0);
+        assert_eq!(show.tick(&machine), Some(1));
+    }
+
+    #[test]
+    fn fades_hold_the_next_entry_back() {
+        let config = Config::default();
+        let mut machine = TuringMachine::pinned(1, "seed", "RL", &config);
+        let mut show = slideshow(Transition::Fade);
+        machine.steps = 100;
+        assert_eq!(show.tick(&machine), None);
+        assert!(show.fade_level().is_some_and(|level| level < 1.0));
+        assert_eq!(show.tick(&machine), None);
+        show.started(1);
+        assert_eq!(show.fade_level(), None);
+
+        // Pausing calls off a fade that's under way
+        machine.steps = 100;
+        assert_eq!(show.tick(&machine), None);
+        show.pause();
+        assert_eq!((show.fade_level(), show.tick(&machine)), (None, None));
+        assert!(show.paused());
+    }
+}
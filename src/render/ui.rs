@@ -1,12 +1,16 @@
 use ratatui::{
     layout::{Constraint, Direction as LayoutDirection, Layout, Rect, Alignment},
     style::{Color, Style, Modifier},
-    widgets::{Block, Borders, Paragraph, Clear, Wrap},
+    widgets::{Block, Borders, Paragraph, Clear, Sparkline, Wrap},
     text::{Line, Span},
     symbols::border,
     Frame,
 };
 use super::App;
+use crate::config::HeadSchedule;
+use crate::machine::{DetectionStatus, Grid, HeadActivity, TuringMachine};
+use crate::machine::detection::group_digits;
+use crate::machine::stats::{RunStats, TURN_NAMES};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PopupPosition {
@@ -154,24 +158,7 @@ pub fn render_popup(f: &mut Frame, content: Vec<Line>, config: PopupConfig) {
         PopupPosition::BottomLeft => bottom_left_rect_fixed_size(popup_width, popup_height, area),
     };
     
-    f.render_widget(Clear, popup_area);
-
-    let block = if config.title.is_empty() {
-        Block::default()
-            .borders(Borders::ALL)
-            .border_set(border::ROUNDED)
-            .border_style(config.border_style)
-            .style(config.background_style)
-    } else {
-        Block::default()
-            .borders(Borders::ALL)
-            .border_set(border::ROUNDED)
-            .title(format!(" {} ", config.title))
-            .title_alignment(Alignment::Left)
-            .title_style(config.title_style)
-            .border_style(config.border_style)
-            .style(config.background_style)
-    };
+    render_frame(f, popup_area, &config);
 
     // Content area
     let content_area = Rect {
@@ -181,9 +168,6 @@ pub fn render_popup(f: &mut Frame, content: Vec<Line>, config: PopupConfig) {
         height: popup_area.height.saturating_sub(2 + config.padding),
     };
 
-    // Render background
-    f.render_widget(block, popup_area);
-
     // Render content
     let formatted_content: Vec<Line> = content.into_iter().map(|line| {
         let spans: Vec<Span> = line.spans.into_iter().map(|span| {
@@ -206,6 +190,25 @@ pub fn render_popup(f: &mut Frame, content: Vec<Line>, config: PopupConfig) {
     f.render_widget(paragraph, content_area);
 }
 
+// Clears `area` and draws a popup's border, title and background over it, returning the area inside the border
+fn render_frame(f: &mut Frame, area: Rect, config: &PopupConfig) -> Rect {
+    f.render_widget(Clear, area);
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(config.border_style)
+        .style(config.background_style);
+    if !config.title.is_empty() {
+        block = block
+            .title(format!(" {} ", config.title))
+            .title_alignment(Alignment::Left)
+            .title_style(config.title_style);
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    inner
+}
+
 fn centered_rect_fixed_size(width: u16, height: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(LayoutDirection::Vertical)
@@ -286,9 +289,22 @@ pub fn render_help_overlay(f: &mut Frame, app: &App) {
         Line::from(format!("{}: Reload config", app.config.controls.config_reload)),
         Line::from(format!("{}: Toggle help", app.config.controls.help)),
         Line::from(format!("{}: Toggle statusbar", app.config.controls.statusbar)),
+        Line::from(format!("{}: Toggle stats", app.config.controls.stats)),
+        Line::from(format!("{}: Toggle heatmap", app.config.controls.heatmap)),
         Line::from(format!("{}: Random seed", app.config.controls.randomize_seed)),
         Line::from(format!("{}: Random rule", app.config.controls.randomize_rule)),
         Line::from("R: Random seed & rule"),
+        Line::from(format!("{}: Cycle split-screen universes", app.config.controls.split)),
+        Line::from(format!("{}: Toggle universe diff", app.config.controls.diff)),
+        Line::from(format!("{}: Browse presets", app.config.controls.presets)),
+        Line::from(format!("{}: Browse run history", app.config.controls.history)),
+        Line::from(format!("{}: Bookmark current run", app.config.controls.favourite)),
+        Line::from(format!("{}/{}: Previous/next run", app.config.controls.history_back, app.config.controls.history_forward)),
+        Line::from(format!("{}/{}: Previous/next playlist entry", app.config.controls.playlist_previous, app.config.controls.playlist_next)),
+        Line::from(format!("{}: Pause playlist", app.config.controls.playlist_pause)),
+        Line::from(format!("{}: Toggle mouse mode", app.config.controls.mouse)),
+        Line::from(format!("{}: Cycle paint state", app.config.controls.paint_state)),
+        Line::from(format!("{}: Rotate placed head direction", app.config.controls.head_direction)),
         Line::from(""),
         Line::from(vec![Span::styled("Head Count", Style::default().add_modifier(Modifier::BOLD))]),
         Line::from(""),
@@ -332,20 +348,391 @@ pub fn render_statusbar_overlay(f: &mut Frame, app: &App) {
         app.machine.auto_halted,
     );
     
+    // Cycles can't be proven for stochastic rules
+    let rule_note = if app.machine.is_stochastic() { " (stochastic)" } else { "" };
+    let schedule_note = match app.config.simulation.schedule {
+        HeadSchedule::Sequential => "",
+        HeadSchedule::Lockstep => " (lockstep)",
+        HeadSchedule::Random => " (random order)",
+    };
+
+    let detection_note = match app.machine.detector.status() {
+        DetectionStatus::Running => String::new(),
+        status => format!(" | {}", status),
+    };
+
+    // Heads stuck on their own while the run goes on
+    let stuck_heads = app.machine.heads.iter()
+        .filter(|head| head.watch.activity() != HeadActivity::Active)
+        .count();
+    let stuck_note = if stuck_heads > 0 { format!(", {} stuck", stuck_heads) } else { String::new() };
+
+    let playlist_note = match &app.slideshow {
+        Some(slideshow) => format!(
+            " | Playlist: {}/{}{}",
+            slideshow.position + 1, slideshow.len(), if slideshow.paused() { " (paused)" } else { "" },
+        ),
+        None => String::new(),
+    };
+
     let status_text = format!(
-        "{} | Heads: {} | Steps: {} | Speed: {} | Rule: {} | Seed: {}",
+        "{} | Heads: {}{}{} | Steps: {} | Speed: {} | Rule: {}{} | Seed: {}{}{} | Behaviour: {}{}",
         running_text,
         app.machine.num_heads,
+        stuck_note,
+        schedule_note,
         app.machine.steps,
         current_speed,
         app.machine.rule_string,
-        app.machine.current_seed
+        rule_note,
+        app.machine.current_seed,
+        if app.is_favourite() { " ★" } else { "" },
+        detection_note,
+        app.machine.behaviour.behaviour(),
+        playlist_note,
     );
 
     let content = vec![Line::from(status_text)];
     render_popup(f, content, PopupConfig::statusbar());
 }
 
+// Grid and label areas per universe: 2 and 3 side by side, 4 as a 2x2 grid
+pub fn universe_layout(area: Rect, count: usize) -> Vec<(Rect, Option<Rect>)> {
+    if count <= 1 {
+        return vec![(area, None)];
+    }
+
+    let (rows, columns) = if count == 4 { (2, 2) } else { (1, count) };
+    let row_areas = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(area);
+
+    row_areas.iter()
+        .flat_map(|&row| {
+            Layout::default()
+                .direction(LayoutDirection::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+                .split(row)
+                .to_vec()
+        })
+        .take(count)
+        .map(|tile| {
+            let grid = Rect { height: tile.height.saturating_sub(1), ..tile };
+            let label = Rect { y: tile.y + grid.height, height: tile.height.min(1), ..tile };
+            (grid, Some(label))
+        })
+        .collect()
+}
+
+pub fn render_universe_label(f: &mut Frame, machine: &TuringMachine, index: usize, diff: Option<usize>, area: Rect) {
+    let running_text = status_label(machine.running, machine.has_looped, machine.auto_halted);
+    let diff_note = diff.map(|cells| format!(" | Diff: {}", cells)).unwrap_or_default();
+    let label = format!(
+        "[{}] {} | Steps: {} | Heads: {} | Rule: {} | Seed: {}{}",
+        index + 1,
+        running_text,
+        machine.steps,
+        machine.num_heads,
+        machine.rule_string,
+        machine.current_seed,
+        diff_note
+    );
+
+    let paragraph = Paragraph::new(label)
+        .style(Style::default().fg(Color::White).bg(Color::Black).add_modifier(Modifier::REVERSED));
+    f.render_widget(paragraph, area);
+}
+
+// Heads listed one per line before the rest are summed up
+const STATS_HEAD_LINES: usize = 4;
+const SPARKLINE_HEIGHT: u16 = 5;
+
+fn stats_lines(stats: &RunStats) -> Vec<Line<'static>> {
+    let (width, height) = stats.bounds
+        .map_or((0, 0), |(min_x, min_y, max_x, max_y)| (max_x - min_x + 1, max_y - min_y + 1));
+    // Visits are only counted while this overlay is open
+    let since = stats.visits_since()
+        .map_or(String::new(), |step| format!(" since step {}", group_digits(step)));
+    let mut lines = vec![
+        Line::from(format!(
+            "Population: {} | Visited: {} | Bounds: {}x{}{}",
+            group_digits(stats.population()),
+            group_digits(stats.visited() as u64),
+            width,
+            height,
+            since
+        )),
+        Line::from(format!("Throughput: {} steps/s", group_digits(stats.throughput() as u64))),
+    ];
+
+    let cells: Vec<String> = stats.histogram()
+        .map(|(state, count)| format!("{} {}", state, group_digits(count)))
+        .collect();
+    if !cells.is_empty() {
+        lines.push(Line::from(format!("Cells: {}", cells.join(" | "))));
+    }
+
+    let turns: Vec<String> = TURN_NAMES.iter().zip(stats.turns)
+        .filter(|&(_, count)| count > 0)
+        .map(|(name, count)| format!("{} {}", name.replace('_', "-"), group_digits(count)))
+        .collect();
+    if !turns.is_empty() {
+        lines.push(Line::from(format!("Turns: {}", turns.join(" | "))));
+    }
+
+    for (index, head) in stats.heads.iter().enumerate().take(STATS_HEAD_LINES) {
+        lines.push(Line::from(format!(
+            "Head {}: travelled {}, net ({}, {})",
+            index + 1,
+            group_digits(head.distance),
+            head.displacement.0,
+            head.displacement.1
+        )));
+    }
+    if stats.heads.len() > STATS_HEAD_LINES {
+        lines.push(Line::from(format!("... and {} more heads", stats.heads.len() - STATS_HEAD_LINES)));
+    }
+    lines
+}
+
+pub fn render_stats_overlay(f: &mut Frame, app: &App) {
+    let config = PopupConfig {
+        title: "Stats".to_string(),
+        title_style: Style::default().add_modifier(Modifier::BOLD).fg(Color::Rgb(230, 180, 90)),
+        border_style: Style::default().fg(Color::Rgb(180, 140, 70)),
+        background_style: Style::default().bg(Color::Rgb(32, 28, 24)),
+        content_style: Style::default().fg(Color::Rgb(240, 225, 200)),
+        ..Default::default()
+    };
+    let stats = &app.machine.stats;
+    let lines = stats_lines(stats);
+
+    let area = f.area();
+    let content_width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16;
+    let width = (content_width + 4).max(40).min(area.width);
+    let height = (lines.len() as u16 + SPARKLINE_HEIGHT + 3).min(area.height);
+    let popup_area = centered_rect_fixed_size(width, height, area);
+
+    let inner = render_frame(f, popup_area, &config);
+
+    let sections = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([
+            Constraint::Length(lines.len() as u16),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(Rect { x: inner.x + 1, width: inner.width.saturating_sub(2), ..inner });
+
+    f.render_widget(Paragraph::new(lines).style(config.content_style), sections[0]);
+    f.render_widget(
+        Paragraph::new("Population over time").style(config.content_style.add_modifier(Modifier::DIM)),
+        sections[1],
+    );
+
+    // Newest samples on the right, as many as fit
+    let history = &stats.history;
+    let visible = &history[history.len().saturating_sub(sections[2].width as usize)..];
+    let sparkline = Sparkline::default()
+        .data(visible)
+        .style(Style::default().fg(Color::Rgb(230, 180, 90)));
+    f.render_widget(sparkline, sections[2]);
+}
+
+// Name, description and what the selected preset sets
+fn preset_lines(app: &App) -> Vec<Line<'static>> {
+    let Some(preset) = app.presets.get(app.preset_selection) else {
+        return vec![Line::from("No presets found")];
+    };
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::from(Span::styled(preset.name.clone(), bold)),
+        Line::from(preset.description.clone()),
+        Line::from(""),
+        Line::from(format!("Rule: {}", preset.rule().unwrap_or("unchanged"))),
+        Line::from(format!("Seed: {}", preset.seed().unwrap_or("random"))),
+        Line::from(format!("Heads: {}", preset.heads().map_or("unchanged".to_string(), |h| h.to_string()))),
+    ];
+    let swatches: Vec<Span> = preset.colors().into_iter()
+        .map(|color| Span::styled("██", Style::default().fg(app.config.parse_color(color))))
+        .collect();
+    if !swatches.is_empty() {
+        lines.push(Line::from([vec![Span::raw("Colors: ")], swatches].concat()));
+    }
+    lines.push(Line::from(if preset.builtin { "Built in" } else { "From your presets folder" }));
+    lines
+}
+
+pub fn render_preset_overlay(f: &mut Frame, app: &App) {
+    let config = PopupConfig {
+        title: "Presets".to_string(),
+        ..PopupConfig::help()
+    };
+    let names: Vec<Line> = app.presets.iter().enumerate()
+        .map(|(index, preset)| {
+            let style = if index == app.preset_selection {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(format!(" {} ", preset.name), style))
+        })
+        .collect();
+    let details = preset_lines(app);
+    let mut footer = vec![Line::from(Span::styled(
+        "↑/↓ select  Enter load  Esc close",
+        Style::default().add_modifier(Modifier::DIM),
+    ))];
+    footer.extend(app.preset_errors.iter().map(|e| {
+        Line::from(Span::styled(format!("• {}", e), Style::default().fg(Color::Rgb(255, 99, 99))))
+    }));
+
+    let area = f.area();
+    let list_width = names.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 2;
+    let details_width = details.iter().chain(&footer).map(|line| line.width()).max().unwrap_or(0) as u16;
+    let width = (list_width + details_width + 7).max(40).min(area.width);
+    let body = names.len().max(details.len()) as u16;
+    let height = (body + footer.len() as u16 + 3).min(area.height);
+    let popup_area = centered_rect_fixed_size(width, height, area);
+
+    let inner = render_frame(f, popup_area, &config);
+
+    let rows = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(footer.len() as u16 + 1)])
+        .split(Rect { x: inner.x + 1, width: inner.width.saturating_sub(2), ..inner });
+    let columns = Layout::default()
+        .direction(LayoutDirection::Horizontal)
+        .constraints([Constraint::Length(list_width), Constraint::Min(1)])
+        .split(rows[0]);
+
+    // Keep the selection in view on short terminals
+    let scroll = (app.preset_selection as u16 + 1).saturating_sub(columns[0].height);
+    f.render_widget(Paragraph::new(names).style(config.content_style).scroll((scroll, 0)), columns[0]);
+    f.render_widget(
+        Paragraph::new(details).style(config.content_style).wrap(Wrap { trim: true }),
+        Rect { x: columns[1].x + 2, width: columns[1].width.saturating_sub(2), ..columns[1] },
+    );
+    f.render_widget(
+        Paragraph::new([vec![Line::from("")], footer].concat()).style(config.content_style),
+        rows[1],
+    );
+}
+
+// One run per line, newest first
+fn history_lines(app: &App) -> Vec<Line<'static>> {
+    let Some(history) = app.history.as_ref().filter(|h| !h.runs.is_empty()) else {
+        return vec![Line::from("No runs yet")];
+    };
+    history.runs.iter().enumerate().rev().enumerate()
+        .map(|(row, (index, run))| {
+            let mut style = if row == app.history_selection {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            if app.history_position == Some(index) {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            let rule: String = run.rule.chars().take(24).collect();
+            Line::from(Span::styled(
+                format!(
+                    " {} {:<24} {:<12} {:>3} heads {:>10} steps  {} ",
+                    if run.favourite { "★" } else { " " },
+                    rule, run.seed, run.heads, run.steps, run.outcome,
+                ),
+                style,
+            ))
+        })
+        .collect()
+}
+
+pub fn render_history_overlay(f: &mut Frame, app: &App) {
+    let config = PopupConfig {
+        title: format!("History ({} runs)", app.history.as_ref().map_or(0, |h| h.runs.len())),
+        ..PopupConfig::help()
+    };
+    let lines = history_lines(app);
+    let footer = Line::from(Span::styled(
+        format!("↑/↓ select  Enter replay  {} bookmark  Esc close", app.config.controls.favourite),
+        Style::default().add_modifier(Modifier::DIM),
+    ));
+
+    let area = f.area();
+    let width = (lines.iter().chain([&footer]).map(|line| line.width()).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let height = (lines.len() as u16 + 5).min(area.height);
+    let popup_area = centered_rect_fixed_size(width, height, area);
+
+    let inner = render_frame(f, popup_area, &config);
+
+    let rows = Layout::default()
+        .direction(LayoutDirection::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(Rect { x: inner.x + 1, width: inner.width.saturating_sub(2), ..inner });
+    // Keep the selection in view on short terminals
+    let scroll = (app.history_selection as u16 + 1).saturating_sub(rows[0].height);
+    f.render_widget(Paragraph::new(lines).style(config.content_style).scroll((scroll, 0)), rows[0]);
+    f.render_widget(Paragraph::new(vec![Line::from(""), footer]).style(config.content_style), rows[1]);
+}
+
+// Cell, visit count while the heatmap is shown and heads under the pointer
+fn tooltip_lines(machine: &TuringMachine, (x, y): (i32, i32)) -> Vec<Line<'static>> {
+    let state = machine.grid.get_cell(x, y);
+    let mut lines = vec![
+        Line::from(format!("Cell ({}, {}): {}", x, y, if state == Grid::EMPTY { "blank".to_string() } else { state.to_string() })),
+    ];
+    if machine.grid.counts_visits() {
+        lines.push(Line::from(format!("Visits: {}", machine.grid.visits.get(&(x, y)).copied().unwrap_or(0))));
+    }
+    for (index, head) in machine.heads.iter().enumerate().filter(|(_, h)| (h.x, h.y) == (x, y)) {
+        lines.push(Line::from(format!("Head {}: state {}, facing {:?}", index + 1, head.internal_state, head.direction)));
+    }
+    lines
+}
+
+pub fn render_mouse_overlay(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let hint = format!(
+        " Mouse: paint {} ({}), heads face {:?} ({}/scroll) ",
+        app.paint_state_name(), app.config.controls.paint_state,
+        app.head_direction, app.config.controls.head_direction,
+    );
+    let hint_area = Rect { height: area.height.min(1), width: (hint.len() as u16).min(area.width), ..area };
+    f.render_widget(Clear, hint_area);
+    f.render_widget(
+        Paragraph::new(hint).style(Style::default().fg(Color::Rgb(220, 235, 255)).bg(Color::Rgb(24, 28, 32))),
+        hint_area,
+    );
+
+    let Some((x, y)) = app.hover else {
+        return;
+    };
+    let (grid_area, _) = universe_layout(area, 1 + app.universes.len())[0];
+    let lines = tooltip_lines(&app.machine, (x, y));
+    let width = (lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+
+    // Beside the pointer, flipped to the other side near the right and bottom edges
+    let column = grid_area.x + x as u16 * 2;
+    let row = grid_area.y + y as u16;
+    let left = if column + 2 + width <= area.right() { column + 2 } else { column.saturating_sub(width) };
+    let top = if row + 1 + height <= area.bottom() { row + 1 } else { row.saturating_sub(height) };
+    let tooltip_area = Rect { x: left, y: top, width, height }.intersection(area);
+
+    let config = PopupConfig {
+        title: String::new(),
+        border_style: Style::default().fg(Color::Rgb(100, 200, 255)),
+        background_style: Style::default().bg(Color::Rgb(24, 28, 32)),
+        ..Default::default()
+    };
+    let inner = render_frame(f, tooltip_area, &config);
+    f.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(Color::Rgb(220, 235, 255))),
+        Rect { x: inner.x + 1, width: inner.width.saturating_sub(1), ..inner },
+    );
+}
+
 pub fn render_keycast_overlay(f: &mut Frame, app: &App) {
     if let Some(ref keypress) = app.last_keypress {
         let content = vec![Line::from(keypress.clone())];
@@ -366,4 +753,18 @@ mod tests {
         assert_eq!(status_label(false, true, true), "Halted");
         assert_eq!(status_label(false, false, true), "Halted");
     }
+
+    #[test]
+    fn universe_layout_tiles() {
+        let area = Rect::new(0, 0, 80, 40);
+        assert_eq!(universe_layout(area, 1), vec![(area, None)]);
+
+        let pair = universe_layout(area, 2);
+        assert_eq!(pair.len(), 2);
+        assert_eq!(pair[0].0, Rect::new(0, 0, 40, 39));
+        assert_eq!(pair[1].1, Some(Rect::new(40, 39, 40, 1)));
+
+        let quad = universe_layout(area, 4);
+        assert_eq!(quad[3].0, Rect::new(40, 20, 40, 19));
+    }
 }